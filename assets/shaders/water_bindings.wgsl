@@ -1,6 +1,17 @@
 #define_import_path bevy_water::water_bindings
 
+struct WakeData {
+  position: vec2<f32>,
+  velocity: vec2<f32>,
+  hull_length: f32,
+  hull_width: f32,
+  strength: f32,
+  foam: f32,
+};
+
 struct WaterMaterial {
+  // First, so the struct array is aligned to 16 bytes.
+  wakes: array<WakeData, 8>,
   deep_color: vec4<f32>,
   shallow_color: vec4<f32>,
   edge_color: vec4<f32>,
@@ -12,7 +23,41 @@ struct WaterMaterial {
   wave_blend: f32,
   wave_dir_a: vec2<f32>,
   wave_dir_b: vec2<f32>,
+  refraction_strength: f32,
+  refraction_dispersion: f32,
+  subsurface_strength: f32,
+  subsurface_color: vec4<f32>,
+  detail_normal_scale: vec2<f32>,
+  detail_normal_strength: f32,
+  detail_normal_speed: f32,
+  detail_fade_distance: f32,
+  flow_velocity: vec2<f32>,
+  flow_map_offset: vec2<f32>,
+  flow_map_size: vec2<f32>,
+  flow_map_scale: f32,
+  flow_period: f32,
+  sphere_center: vec3<f32>,
+  sphere_radius: f32,
+  ripple_offset: vec2<f32>,
+  ripple_size: vec2<f32>,
+  wake_count: u32,
+  rain_intensity: f32,
+  rain_ripple_size: f32,
+  rain_ripple_strength: f32,
+  wave_length: f32,
+  wave_speed: f32,
+  wave_time_offset: f32,
 };
 
 @group(#{MATERIAL_BIND_GROUP}) @binding(100)
 var<uniform> material: WaterMaterial;
+@group(#{MATERIAL_BIND_GROUP}) @binding(101)
+var detail_normal_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(102)
+var detail_normal_sampler: sampler;
+@group(#{MATERIAL_BIND_GROUP}) @binding(103)
+var flow_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(104)
+var flow_sampler: sampler;
+@group(#{MATERIAL_BIND_GROUP}) @binding(105)
+var ripple_texture: texture_2d<f32>;
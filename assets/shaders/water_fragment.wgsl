@@ -25,6 +25,88 @@
 #import bevy_water::water_bindings
 #import bevy_water::water_functions as water_fn
 
+#ifndef PREPASS_PIPELINE
+#import bevy_pbr::mesh_view_bindings as view_bindings
+
+// Sunlight scattered through the wave crests towards the viewer.
+fn subsurface_scattering(height: f32, N: vec3<f32>, V: vec3<f32>) -> vec3<f32> {
+  let strength = water_bindings::material.subsurface_strength;
+  if strength <= 0.0 {
+    return vec3<f32>(0.0);
+  }
+  // Wave crests are thinner, so more light makes it through them.
+  let crest = smoothstep(-0.5, 0.5, height / max(water_bindings::material.amplitude, 0.0001));
+  let NdotV = saturate(dot(N, V));
+
+  var light = vec3<f32>(0.0);
+  let n_directional_lights = view_bindings::lights.n_directional_lights;
+  for (var i: u32 = 0u; i < n_directional_lights; i = i + 1u) {
+    let directional_light = view_bindings::lights.directional_lights[i];
+    let L = directional_light.direction_to_light;
+    // Looking towards the light, through the back of the wave.
+    let through = pow(saturate(dot(V, -L)), 4.0);
+    // Wave faces turned away from the light.
+    let back = pow(saturate(0.5 - 0.5 * dot(L, N)), 3.0);
+    // Some light is scattered in all directions from inside the wave.
+    let ambient = 0.1 * NdotV * NdotV * saturate(L.y);
+    light += directional_light.color.rgb * (crest * through * back + crest * ambient);
+  }
+
+  return light * water_bindings::material.subsurface_color.rgb * strength * view_bindings::view.exposure;
+}
+#endif
+
+#ifdef WATER_REFRACTION
+#ifndef PREPASS_PIPELINE
+#ifdef TONEMAP_IN_SHADER
+#import bevy_core_pipeline::tonemapping::approximate_inverse_tone_mapping
+#endif
+
+// Per-channel light absorption of water (red is absorbed faster than blue).
+const WATER_ABSORPTION: vec3<f32> = vec3<f32>(0.45, 0.12, 0.06);
+
+// Sample the opaque scene behind the water.
+fn sample_scene(frag_coord: vec4<f32>, uv: vec2<f32>) -> vec3<f32> {
+  let viewport_size = view_bindings::view.viewport.zw;
+  var sample_uv = uv;
+#ifdef DEPTH_PREPASS
+#ifndef WEBGL2
+  // Don't refract objects that are in front of the water surface.
+  if bevy_pbr::prepass_utils::prepass_depth(vec4<f32>(uv * viewport_size, 0.0, 0.0), 0u) > frag_coord.z {
+    sample_uv = frag_coord.xy / viewport_size;
+  }
+#endif
+#endif
+  var color = textureSampleLevel(
+    view_bindings::view_transmission_texture,
+    view_bindings::view_transmission_sampler,
+    sample_uv,
+    0.0
+  );
+#ifdef TONEMAP_IN_SHADER
+  color = approximate_inverse_tone_mapping(color, view_bindings::view.color_grading);
+#endif
+  // The transmission texture is already exposure-adjusted.
+  return color.rgb / view_bindings::view.exposure;
+}
+
+// Sample the opaque scene offset by the wave normal (relative to the undisturbed surface).
+fn refracted_scene(frag_coord: vec4<f32>, wave_normal: vec3<f32>, depth: f32) -> vec3<f32> {
+  let view_offset = (view_bindings::view.view_from_world * vec4<f32>(wave_normal, 0.0)).xy;
+  // Fade the distortion out near the shore line.
+  let strength = water_bindings::material.refraction_strength * saturate(depth);
+  let offset = view_offset * vec2<f32>(1.0, -1.0) * strength;
+  let dispersion = water_bindings::material.refraction_dispersion;
+  let uv = frag_coord.xy / view_bindings::view.viewport.zw;
+
+  let r = sample_scene(frag_coord, uv + offset * (1.0 - dispersion)).r;
+  let g = sample_scene(frag_coord, uv + offset).g;
+  let b = sample_scene(frag_coord, uv + offset * (1.0 + dispersion)).b;
+  return vec3<f32>(r, g, b);
+}
+#endif
+#endif
+
 @fragment
 fn fragment(
 #ifdef MESHLET_MESH_MATERIAL_PASS
@@ -41,19 +123,54 @@ fn fragment(
 
   var in = p_in;
   var world_position: vec4<f32> = in.world_position;
-  let w_pos = water_fn::uv_to_coord(in.uv);
-  // Calculate normal.
+  let surface_normal = normalize(in.world_normal);
+#ifdef WATER_CUBE
+  let cube_coord = water_fn::uv_to_cube_coord(in.uv);
+  let height = water_fn::get_cube_wave_height(cube_coord);
+#else
+#ifdef WATER_SPHERE
+  let radial = water_fn::sphere_radial(world_position.xyz);
+  let height = water_fn::get_sphere_wave_height(radial);
+#else
+  let w_pos = water_fn::surface_coord(in.uv, world_position.xyz);
   let height = water_fn::get_wave_height(w_pos);
+#endif
+#endif
+  // Calculate normal.
 #if QUALITY > 2
+#ifdef WATER_CUBE
+  in.world_normal = water_fn::get_cube_wave_normal(cube_coord, surface_normal, height);
+#else
+#ifdef WATER_SPHERE
+  in.world_normal = water_fn::get_sphere_wave_normal(radial, height);
+#else
   let delta = 0.5;
   let height_dx = water_fn::get_wave_height(w_pos + vec2<f32>(delta, 0.0));
   let height_dz = water_fn::get_wave_height(w_pos + vec2<f32>(0.0, delta));
   in.world_normal = normalize(vec3<f32>(height - height_dx, delta, height - height_dz));
+#endif
+#endif
 #else
   let pos = world_position.xyz + (in.world_normal * height);
   let pos_dx = dpdx(pos);
   let pos_dy = dpdy(pos);
   in.world_normal = normalize(cross(pos_dy, pos_dx));
+#endif
+#ifdef WATER_DETAIL_NORMALS
+#ifndef WATER_SPHERE
+#ifndef WATER_CUBE
+  let view_distance = length(bevy_pbr::view_transformations::position_world_to_view(world_position.xyz));
+  in.world_normal = water_fn::apply_detail_normals(in.world_normal, w_pos, view_distance);
+#endif
+#endif
+#endif
+#ifdef WATER_RAIN
+#ifndef WATER_SPHERE
+#ifndef WATER_CUBE
+  let rain_distance = length(bevy_pbr::view_transformations::position_world_to_view(world_position.xyz));
+  in.world_normal = water_fn::apply_rain_normals(in.world_normal, w_pos, rain_distance);
+#endif
+#endif
 #endif
  
   // If we're in the crossfade section of a visibility range, conditionally
@@ -67,6 +184,12 @@ fn fragment(
 
   let deep_color = water_bindings::material.deep_color;
   var water_color = deep_color;
+  // Distance through the water to the opaque geometry behind it.
+  var water_depth = 1.0;
+#ifdef WATER_REFRACTION
+  // Without the depth prepass the opacity only depends on the clarity.
+  water_color.a = 1.0 - exp(-water_depth * water_bindings::material.clarity);
+#endif
 #ifdef DEPTH_PREPASS
 #ifndef PREPASS_PIPELINE
 #ifndef WEBGL2
@@ -79,6 +202,7 @@ fn fragment(
   let z_depth_buffer_view = depth_ndc_to_view_z(z_depth_buffer_ndc);
   let z_fragment_view = depth_ndc_to_view_z(in.position.z);
   let depth_diff_view = z_fragment_view - z_depth_buffer_view;
+  water_depth = depth_diff_view;
   let beers_law = exp(-depth_diff_view * water_clarity);
   let depth_color = vec4<f32>(mix(deep_color.xyz, shallow_color.xyz, beers_law), 1.0 - beers_law);
   water_color = mix(edge_color, depth_color, smoothstep(0.0, edge_scale, depth_diff_view));
@@ -86,13 +210,30 @@ fn fragment(
 #endif
 #endif
   pbr_input.material.base_color *= water_color;
+#ifdef WATER_WAKES
+  // Foam trails of the wakes.
+  let wake_foam = water_fn::get_wakes(w_pos).y;
+  let foam_color = vec4<f32>(water_bindings::material.edge_color.rgb, 1.0);
+  pbr_input.material.base_color = mix(pbr_input.material.base_color, foam_color, wake_foam);
+#endif
 
   //let foam_color = water_bindings::material.edge_color;
   //let foam = mix(foam_color, depth_color, smoothstep(0.0, edge_scale, depth_diff_view));
 
+#ifdef WATER_REFRACTION
+  // Opacity of the water surface, `alpha_discard` sets it to 1.0 for opaque materials.
+  let water_opacity = pbr_input.material.base_color.a;
+#endif
+
   // alpha discard
   pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
 
+#ifdef WATER_REFRACTION
+  // The `specular_transmission` is only used to get the opaque scene texture,
+  // the refraction is handled below.
+  pbr_input.material.specular_transmission = 0.0;
+#endif
+
 #ifdef PREPASS_PIPELINE
   // write the gbuffer, lighting pass id, and optionally normal and motion_vector textures
   let out = deferred_output(in, pbr_input);
@@ -102,10 +243,18 @@ fn fragment(
   var out: FragmentOutput;
   if (pbr_input.material.flags & STANDARD_MATERIAL_FLAGS_UNLIT_BIT) == 0u {
     out.color = apply_pbr_lighting(pbr_input);
+    out.color = vec4<f32>(out.color.rgb + subsurface_scattering(height, pbr_input.N, pbr_input.V), out.color.a);
   } else {
     out.color = pbr_input.material.base_color;
   }
 
+#ifdef WATER_REFRACTION
+  // Blend the lit water surface over the refracted scene, instead of using alpha blending.
+  let scene = refracted_scene(in.position, in.world_normal - surface_normal, water_depth);
+  let transmittance = exp(-water_depth * water_bindings::material.clarity * WATER_ABSORPTION);
+  out.color = vec4<f32>(mix(scene * transmittance, out.color.rgb, water_opacity), 1.0);
+#endif
+
   // apply in-shader post processing (fog, alpha-premultiply, and also tonemapping, debanding if the camera is non-hdr)
   // note this does not include fullscreen postprocessing effects like bloom.
   out.color = main_pass_post_lighting_processing(pbr_input, out.color);
@@ -7,12 +7,20 @@
 #import bevy_pbr::mesh_view_bindings::globals
 #endif
 
-#import bevy_water::water_bindings::material
+#import bevy_water::water_bindings::{
+  material, WakeData, detail_normal_texture, detail_normal_sampler, flow_texture, flow_sampler,
+  ripple_texture,
+}
 #import bevy_water::noise;
 
+// Wave animation time (matches `GlobalWaveState::wave_time`).
+fn get_wave_time() -> f32 {
+  return globals.time * material.wave_speed + material.wave_time_offset;
+}
+
 fn wave(p: vec2<f32>) -> f32 {
   // Internal time creates fluid oscillation within the pattern
-  let time = globals.time * 0.5 + 23.0;
+  let time = get_wave_time() * 0.5 + 23.0;
   let time_x = time / 1.0;
   let time_y = time / 0.5;
   // Pattern oriented so primary motion is along X (travel direction after rotation)
@@ -54,8 +62,9 @@ fn sample_directional_wave(p: vec2<f32>, time: f32, dir: vec2<f32>) -> f32 {
 
 const FADE_IN: f32 = 0.85;
 
-fn get_wave_height(p: vec2<f32>) -> f32 {
-  let time = globals.time / 2.0;
+fn sample_wave_height(coord: vec2<f32>) -> f32 {
+  let time = get_wave_time() / 2.0;
+  let p = coord / max(material.wave_length, 0.001);
 
   // Sample wave B.
   var wave_b = sample_directional_wave(p, time, material.wave_dir_b);
@@ -76,6 +85,324 @@ fn get_wave_height(p: vec2<f32>) -> f32 {
   return material.amplitude * wave_b;
 }
 
+// Flow velocity at coord `p`.
+fn get_flow(p: vec2<f32>) -> vec2<f32> {
+  var flow = material.flow_velocity;
+#ifdef WATER_FLOW_MAP
+  let uv = (p - material.flow_map_offset) / material.flow_map_size;
+  let texel = textureSampleLevel(flow_texture, flow_sampler, uv, 0.0).xy * 2.0 - 1.0;
+  flow += texel * material.flow_map_scale;
+#endif
+  return flow;
+}
+
+// Two-phase flow offsets and the blend between them.
+struct FlowPhases {
+  offset_a: vec2<f32>,
+  offset_b: vec2<f32>,
+  blend: f32,
+}
+
+fn get_flow_phases(p: vec2<f32>) -> FlowPhases {
+  let flow = get_flow(p);
+  let period = material.flow_period;
+  // No flow without a period (matches `sample_flow_advected`).
+  if period <= 0.0 {
+    return FlowPhases(vec2<f32>(0.0), vec2<f32>(0.0), 0.0);
+  }
+  let phase_a = fract(globals.time / period);
+  let phase_b = fract(globals.time / period + 0.5);
+  // Each phase is hidden while it resets.
+  return FlowPhases(flow * phase_a * period, flow * phase_b * period, abs(0.5 - phase_a) * 2.0);
+}
+
+#ifdef WATER_RIPPLES
+fn ripple_texel(i: vec2<i32>, size: vec2<i32>) -> f32 {
+  // Calm water outside of the ripple grid.
+  if any(i < vec2<i32>(0)) || any(i >= size) {
+    return 0.0;
+  }
+  return textureLoad(ripple_texture, i, 0).r;
+}
+
+// Ripple height at coord `p` (bilinear, the float heightfield isn't filterable).
+fn get_ripple_height(p: vec2<f32>) -> f32 {
+  let size = vec2<i32>(textureDimensions(ripple_texture));
+  let texel = (p - material.ripple_offset) / material.ripple_size * vec2<f32>(size) - 0.5;
+  let base = floor(texel);
+  let f = texel - base;
+  let i = vec2<i32>(base);
+  let a = mix(ripple_texel(i, size), ripple_texel(i + vec2<i32>(1, 0), size), f.x);
+  let b = mix(ripple_texel(i + vec2<i32>(0, 1), size), ripple_texel(i + vec2<i32>(1, 1), size), f.x);
+  return mix(a, b, f.y);
+}
+#endif
+
+#ifdef WATER_WAKES
+// Kelvin wake height (`x`) and foam (`y`) at coord `p`.
+fn get_wake(p: vec2<f32>, wake: WakeData) -> vec2<f32> {
+  let speed = length(wake.velocity);
+  let hull_length = max(wake.hull_length, 0.1);
+  if speed < 0.1 {
+    return vec2<f32>(0.0);
+  }
+  let forward = wake.velocity / speed;
+  let d = p - wake.position;
+  // Distance behind the bow and distance from the path.
+  let x = hull_length * 0.5 - dot(d, forward);
+  let y = abs(dot(d, vec2<f32>(-forward.y, forward.x)));
+  if x <= 0.0 {
+    return vec2<f32>(0.0);
+  }
+
+  // The wake grows with the Froude number.
+  let froude = speed / sqrt(9.81 * hull_length);
+  let amount = saturate(froude * 2.5);
+  let ramp = smoothstep(0.0, hull_length * 0.5, x);
+  let decay = inverseSqrt(1.0 + x / hull_length) * (1.0 - smoothstep(6.0, 10.0, x / hull_length));
+
+  // Wave number of the waves travelling with the hull.
+  let k = min(9.81 / (speed * speed), 20.0);
+  // The Kelvin wedge has a half angle of 19.47 degrees.
+  let r = y / (x * 0.3536 + wake.hull_width * 0.5);
+  let wedge = 1.0 - smoothstep(0.8, 1.1, r);
+  // Transverse waves: crests across the path, curving back towards the wedge edges.
+  let transverse = cos(k * x * (1.0 + 0.5 * r * r)) * max(1.0 - r * r, 0.0) * wedge;
+  // Divergent waves: short crests along the edges of the wedge.
+  let edge = exp(-8.0 * (r - 1.0) * (r - 1.0));
+  let divergent = cos(1.5 * k * (x * 0.8165 + y * 0.5774)) * edge;
+  let height = wake.strength * amount * ramp * decay * (0.5 * transverse + divergent);
+
+  // Foam trail behind the hull.
+  let trail = wake.hull_width * 0.5 * (1.0 + 0.15 * x / hull_length);
+  let foam = wake.foam * amount * exp(-(y / trail) * (y / trail)) * exp(-x / (hull_length * 4.0));
+
+  return vec2<f32>(height, min(foam, 1.0));
+}
+
+// Combined wake height (`x`) and foam (`y`) at coord `p`.
+fn get_wakes(p: vec2<f32>) -> vec2<f32> {
+  var wakes = vec2<f32>(0.0);
+  for (var i: u32 = 0u; i < min(material.wake_count, 8u); i = i + 1u) {
+    wakes += get_wake(p, material.wakes[i]);
+  }
+  return vec2<f32>(wakes.x, min(wakes.y, 1.0));
+}
+#endif
+
+fn get_wave_height(p: vec2<f32>) -> f32 {
+#ifdef WATER_FLOW
+  let phases = get_flow_phases(p);
+  let wave_a = sample_wave_height(p - phases.offset_a);
+  let wave_b = sample_wave_height(p - phases.offset_b);
+  var height = mix(wave_a, wave_b, phases.blend);
+#else
+  var height = sample_wave_height(p);
+#endif
+#ifdef WATER_RIPPLES
+  height += get_ripple_height(p);
+#endif
+#ifdef WATER_WAKES
+  height += get_wakes(p).x;
+#endif
+  return height;
+}
+
+// The current wave direction (matches the blend used by `get_wave_height`).
+fn get_wave_direction() -> vec2<f32> {
+  let blend = smoothstep(0.0, FADE_IN, material.wave_blend);
+  return normalize(mix(material.wave_dir_a, material.wave_dir_b, blend) + vec2<f32>(0.0001, 0.0));
+}
+
+fn sample_detail_normal(uv: vec2<f32>) -> vec2<f32> {
+  return textureSample(detail_normal_texture, detail_normal_sampler, uv).xy * 2.0 - 1.0;
+}
+
+// Two scrolling layers of the detail normal map.
+fn get_detail_normal(p: vec2<f32>) -> vec2<f32> {
+  let dir = get_wave_direction();
+  let scroll = dir * globals.time * material.detail_normal_speed;
+  let scale = material.detail_normal_scale;
+
+  // Second layer is rotated and scrolls slower to break up tiling.
+  let rotated_p = vec2<f32>(p.x * 0.8 - p.y * 0.6, p.x * 0.6 + p.y * 0.8);
+  let detail_a = sample_detail_normal(p * scale.x - scroll);
+  let detail_b = sample_detail_normal(rotated_p * scale.y - scroll * 0.5);
+  return detail_a + detail_b;
+}
+
+// Layer the detail normals over the wave normal.
+// `distance` is the distance from the camera, used to fade out the detail.
+fn apply_detail_normals(normal: vec3<f32>, p: vec2<f32>, distance: f32) -> vec3<f32> {
+#ifdef WATER_FLOW
+  let phases = get_flow_phases(p);
+  let detail_a = get_detail_normal(p - phases.offset_a);
+  let detail_b = get_detail_normal(p - phases.offset_b);
+  var detail = mix(detail_a, detail_b, phases.blend);
+#else
+  var detail = get_detail_normal(p);
+#endif
+
+  let fade_distance = material.detail_fade_distance;
+  let fade = 1.0 - smoothstep(fade_distance * 0.5, fade_distance, distance);
+  detail = detail * material.detail_normal_strength * fade;
+
+  // UDN blend (adds the detail slopes to the X/Z of the normal), the water surface is in the XZ plane.
+  return normalize(vec3<f32>(normal.x + detail.x, normal.y, normal.z + detail.y));
+}
+
+#ifdef WATER_RAIN
+fn rain_hash(p: vec2<f32>) -> vec2<f32> {
+  let q = vec2<f32>(dot(p, vec2<f32>(127.1, 311.7)), dot(p, vec2<f32>(269.5, 183.3)));
+  return fract(sin(q) * 43758.5453);
+}
+
+// Normal offset (X/Z) of the raindrop ring ripples at coord `p`.
+//
+// Each ripple cell has at most one raindrop per cycle, at a random position and time.
+fn get_rain_normal(p: vec2<f32>) -> vec2<f32> {
+  let uv = p / max(material.rain_ripple_size, 0.01);
+  let cell = floor(uv);
+  var normal = vec2<f32>(0.0);
+  // The rings grow into the neighbouring cells.
+  for (var y = -1; y <= 1; y = y + 1) {
+    for (var x = -1; x <= 1; x = x + 1) {
+      let c = cell + vec2<f32>(f32(x), f32(y));
+      let h = rain_hash(c);
+      let cycle_time = globals.time * 1.2 + h.y;
+      let cycle = floor(cycle_time);
+      // Only some cells have a raindrop in this cycle.
+      if rain_hash(c + cycle * 7.31).x >= material.rain_intensity {
+        continue;
+      }
+      let t = cycle_time - cycle;
+      let d = uv - (c + rain_hash(c + cycle * 3.17));
+      let dist = length(d);
+      // Expanding ring, fading out as it grows.
+      let x_ring = (dist - t * 1.5) * 12.0;
+      let ring = sin(x_ring) * exp(-x_ring * x_ring * 0.05) * (1.0 - t) * (1.0 - t);
+      normal += d / max(dist, 0.0001) * ring;
+    }
+  }
+  return normal * material.rain_ripple_strength;
+}
+
+// Layer the raindrop ripples over the wave normal.
+// `distance` is the distance from the camera, used to fade out the ripples.
+fn apply_rain_normals(normal: vec3<f32>, p: vec2<f32>, distance: f32) -> vec3<f32> {
+  let fade_distance = material.detail_fade_distance;
+  let fade = 1.0 - smoothstep(fade_distance * 0.25, fade_distance * 0.5, distance);
+  if fade <= 0.0 {
+    return normal;
+  }
+  let rain = get_rain_normal(p) * fade;
+  return normalize(vec3<f32>(normal.x + rain.x, normal.y, normal.z + rain.y));
+}
+#endif
+
+// Triplanar blend weights for the direction `n`.
+fn triplanar_weights(n: vec3<f32>) -> vec3<f32> {
+  let w = pow(abs(n), vec3<f32>(4.0));
+  return w / (w.x + w.y + w.z);
+}
+
+// Wave height at the 3d wave coordinate `q`, blended by the direction `n`.
+// The waves are projected onto the three axis planes, so there are no seams or poles.
+fn get_triplanar_wave_height(q: vec3<f32>, n: vec3<f32>) -> f32 {
+  let w = triplanar_weights(n);
+  return get_wave_height(q.zy) * w.x + get_wave_height(q.xz) * w.y + get_wave_height(q.xy) * w.z;
+}
+
+// Tangent frame around the normal `n`, matches the flat water X/Z axes when `n` is up.
+fn tangent_frame(n: vec3<f32>) -> mat2x3<f32> {
+  var up = vec3<f32>(0.0, 1.0, 0.0);
+  if abs(n.y) > 0.99 {
+    up = vec3<f32>(0.0, 0.0, -1.0);
+  }
+  let tangent = normalize(cross(up, n));
+  return mat2x3<f32>(tangent, cross(tangent, n));
+}
+
+#ifdef WATER_SPHERE
+// Direction from the planet center to `world_position`.
+fn sphere_radial(world_position: vec3<f32>) -> vec3<f32> {
+  return normalize(world_position - material.sphere_center);
+}
+
+// Wave height on the sphere in the direction `n` from the planet center.
+fn get_sphere_wave_height(n: vec3<f32>) -> f32 {
+  return get_triplanar_wave_height(n * material.sphere_radius, n);
+}
+
+// Wave normal on the sphere (matches the flat water normal at the north pole).
+fn get_sphere_wave_normal(n: vec3<f32>, height: f32) -> vec3<f32> {
+  let frame = tangent_frame(n);
+  let delta = 0.5;
+  let step = delta / material.sphere_radius;
+  let height_t = get_sphere_wave_height(normalize(n + frame[0] * step));
+  let height_b = get_sphere_wave_height(normalize(n + frame[1] * step));
+  return normalize(n * delta + frame[0] * (height - height_t) + frame[1] * (height - height_b));
+}
+#endif
+
+#ifdef WATER_CUBE
+// Multi-face coordinates: `uv.x` is `face * 2 + u`, see `CubeFace` for the face order.
+// Returns the point on the cube surface, `coord_scale.x` is the half size of the cube.
+fn uv_to_cube_coord(uv: vec2<f32>) -> vec3<f32> {
+  let face = clamp(floor(uv.x / 2.0), 0.0, 5.0);
+  let st = vec2<f32>(uv.x - face * 2.0, uv.y) * 2.0 - 1.0;
+  var p: vec3<f32>;
+  switch u32(face) {
+    case 0u: { p = vec3<f32>(1.0, -st.y, -st.x); }
+    case 1u: { p = vec3<f32>(-1.0, -st.y, st.x); }
+    case 2u: { p = vec3<f32>(st.x, 1.0, st.y); }
+    case 3u: { p = vec3<f32>(st.x, -1.0, -st.y); }
+    case 4u: { p = vec3<f32>(st.x, -st.y, 1.0); }
+    default: { p = vec3<f32>(-st.x, -st.y, -1.0); }
+  }
+  return p * material.coord_scale.x;
+}
+
+// Wave height at the cube coordinate `c`, continuous across the cube edges.
+fn get_cube_wave_height(c: vec3<f32>) -> f32 {
+  return get_triplanar_wave_height(c, normalize(c));
+}
+
+// Wave normal around the surface normal `n` at the cube coordinate `c`.
+fn get_cube_wave_normal(c: vec3<f32>, n: vec3<f32>, height: f32) -> vec3<f32> {
+  let frame = tangent_frame(n);
+  let delta = 0.5;
+  let height_t = get_cube_wave_height(c + frame[0] * delta);
+  let height_b = get_cube_wave_height(c + frame[1] * delta);
+  return normalize(n * delta + frame[0] * (height - height_t) + frame[1] * (height - height_b));
+}
+#endif
+
+// Wave coordinate of flat water.
+//
+// Cube materials (`WATER_CUBE`) get the planar coordinate on the cube face, the waves
+// don't line up at the cube edges.  Use `get_cube_wave_height(uv_to_cube_coord(uv))` for that.
 fn uv_to_coord(uv: vec2<f32>) -> vec2<f32> {
+#ifdef WATER_CUBE
+  let c = uv_to_cube_coord(uv);
+  let a = abs(c);
+  if a.y >= a.x && a.y >= a.z {
+    return c.xz;
+  }
+  if a.x >= a.z {
+    return c.zy;
+  }
+  return c.xy;
+#else
   return material.coord_offset + (uv * material.coord_scale);
+#endif
+}
+
+// Wave coordinate of a flat water surface point (`uv_to_coord` or the world X/Z position).
+fn surface_coord(uv: vec2<f32>, world_position: vec3<f32>) -> vec2<f32> {
+#ifdef WATER_WORLD_COORDS
+  return material.coord_offset + world_position.xz;
+#else
+  return uv_to_coord(uv);
+#endif
 }
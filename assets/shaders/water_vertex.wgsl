@@ -38,12 +38,31 @@ fn vertex(vertex: Vertex) -> VertexOutput {
 
   // Add the wave height to the world position.
 	var height = -0.5;
+#ifdef WATER_CUBE
+  let cube_coord = water_fn::uv_to_cube_coord(vertex.uv);
+  // Displace away from the cube center, so the faces stay connected at the edges.
+  let radial = normalize(cube_coord);
 #if QUALITY > 2
-  let w_pos = water_fn::uv_to_coord(vertex.uv);
+  height = water_fn::get_cube_wave_height(cube_coord);
+#endif
+  out.world_position = world_position + vec4<f32>((radial * height), 0.);
+#else
+#ifdef WATER_SPHERE
+  // Displace away from the planet center.
+  let radial = water_fn::sphere_radial(world_position.xyz);
+#if QUALITY > 2
+  height = water_fn::get_sphere_wave_height(radial);
+#endif
+  out.world_position = world_position + vec4<f32>((radial * height), 0.);
+#else
+#if QUALITY > 2
+  let w_pos = water_fn::surface_coord(vertex.uv, world_position.xyz);
   height = water_fn::get_wave_height(w_pos);
 #endif
 
   out.world_position = world_position + vec4<f32>((out.world_normal * height), 0.);
+#endif
+#endif
   out.position = position_world_to_clip(out.world_position.xyz);
 
 #ifdef VERTEX_UVS
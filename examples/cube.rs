@@ -6,7 +6,7 @@ use bevy::light::NotShadowCaster;
 use bevy::pbr::wireframe::{Wireframe, WireframePlugin};
 use bevy::{input::common_conditions, prelude::*};
 
-use bevy_water::material::{StandardWaterMaterial, WaterMaterial};
+use bevy_water::material::StandardWaterMaterial;
 use bevy_water::*;
 
 const CUBE_SIZE: f32 = 10.0;
@@ -58,17 +58,11 @@ fn setup(
   mut meshes: ResMut<Assets<Mesh>>,
   mut materials: ResMut<Assets<StandardWaterMaterial>>,
 ) {
-  // Mesh for water.
-  let mesh: Handle<Mesh> = meshes.add(Cuboid::from_length(CUBE_SIZE));
+  // Mesh for water, the UVs encode the cube faces so the waves are seamless across the edges.
+  let builder = CubeWaterMeshBuilder::cube(CUBE_SIZE / 2.0).subdivisions(32);
+  let mesh: Handle<Mesh> = meshes.add(builder.clone());
   // Water material.
-  let material = materials.add(StandardWaterMaterial {
-    base: default(),
-    extension: WaterMaterial {
-      amplitude: settings.amplitude,
-      coord_scale: Vec2::new(256.0, 256.0),
-      ..default()
-    },
-  });
+  let material = materials.add(builder.new_material(&settings));
 
   commands.spawn((
     Name::new(format!("Water world")),
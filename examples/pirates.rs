@@ -233,6 +233,7 @@ impl Ship {
   fn update(
     &self,
     water: &WaterParam,
+    entity: Entity,
     pos: Vec3,
     transform: &mut Transform,
     #[cfg(feature = "debug")] gizmos: &mut Gizmos,
@@ -241,9 +242,14 @@ impl Ship {
     let global = Transform::from_translation(pos).with_rotation(Quat::from_rotation_y(yaw));
 
     // Get the wave position at the front, back_left and back_right.
-    let mut front = water.wave_point(global.transform_point(self.front));
-    let left = water.wave_point(global.transform_point(self.back_left));
-    let right = water.wave_point(global.transform_point(self.back_right));
+    // Ride the wakes of the other ships, not our own.
+    let wave_point = |mut p: Vec3| {
+      p.y = water.wave_height_excluding(p, entity);
+      p
+    };
+    let mut front = wave_point(global.transform_point(self.front));
+    let left = wave_point(global.transform_point(self.back_left));
+    let right = wave_point(global.transform_point(self.back_right));
     let normal = (left - front).cross(right - front).normalize();
 
     // Debug lines.
@@ -265,15 +271,15 @@ impl Ship {
 
 pub fn update_ships(
   water: WaterParam,
-  mut ships: Query<(&Ship, &mut Transform, &GlobalTransform)>,
+  mut ships: Query<(Entity, &Ship, &mut Transform, &GlobalTransform)>,
   #[cfg(feature = "debug")] mut gizmos: Gizmos,
 ) {
-  for (ship, mut transform, global) in ships.iter_mut() {
+  for (entity, ship, mut transform, global) in ships.iter_mut() {
     let pos = global.translation();
     #[cfg(not(feature = "debug"))]
-    ship.update(&water, pos, &mut transform);
+    ship.update(&water, entity, pos, &mut transform);
     #[cfg(feature = "debug")]
-    ship.update(&water, pos, &mut transform, &mut gizmos);
+    ship.update(&water, entity, pos, &mut transform, &mut gizmos);
   }
 }
 
@@ -595,6 +601,8 @@ pub fn setup_ships(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands
       .spawn((
         ship.clone(),
+        // Kelvin wake behind the ship, when it's moving.
+        Wake::new(17.0, 4.0),
         Name::new(format!("Dutch Ship {x}")),
         Transform::from_xyz(-10.0 + (f * 7.8), 0.0, 30.0 + f2)
           .with_rotation(Quat::from_rotation_y(f)),
@@ -82,6 +82,8 @@ fn setup(
     MeshMaterial3d(material),
     Transform::from_xyz(0.0, 0.0, 0.0),
     NotShadowCaster,
+    // Seamless triplanar waves displaced away from the planet center.
+    WaterSphere::new(RADIUS),
   ));
 
   // light
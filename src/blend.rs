@@ -0,0 +1,360 @@
+use bevy::{
+  math::curve::{Curve, EaseFunction},
+  prelude::*,
+};
+
+use crate::{
+  apply_body_settings, material::StandardWaterMaterial, WaterFlow, WaterPreset, WaterSettings,
+  WaveDirection,
+};
+
+/// Blend `from` to `to` at `t` (0.0..1.0) with `ease`.
+///
+/// The wave direction crossfades over the whole `duration` of the blend.  The discrete
+/// fields (grid, quality, textures) switch to `to` at the end (see `WaterSettings::lerp`).
+fn blend_settings(
+  from: &WaterSettings,
+  to: &WaterSettings,
+  t: f32,
+  ease: EaseFunction,
+  duration: f32,
+) -> WaterSettings {
+  let mut settings = from.lerp_switching(to, ease.sample_clamped(t), t >= 1.0);
+  if t < 1.0 {
+    settings.wave_direction_blend_duration = duration;
+  }
+  settings
+}
+
+/// Blend the global `WaterSettings` over time.
+///
+/// Insert this resource to start the blend, it's removed when the blend is done.
+/// Numbers (amplitude, clarity, ...) follow the `ease` curve and colors are blended
+/// in the Oklab color space.  The wave direction crossfades over the whole blend
+/// (see `WaveDirection::set_target`).
+///
+/// ```ignore
+/// // From calm to storm in thirty seconds.
+/// commands.insert_resource(WaterBlend::new(calm, storm, 30.0).with_ease(EaseFunction::SineInOut));
+/// ```
+#[derive(Resource, Clone, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct WaterBlend {
+  /// Settings at the start of the blend.
+  pub from: WaterSettings,
+  /// Settings at the end of the blend.
+  pub to: WaterSettings,
+  /// Duration of the blend in seconds.
+  pub duration: f32,
+  /// Easing curve of the blend.
+  pub ease: EaseFunction,
+  /// Elapsed time in seconds.
+  pub elapsed: f32,
+}
+
+impl WaterBlend {
+  /// Blend from `from` to `to` over `duration` seconds.
+  pub fn new(from: WaterSettings, to: WaterSettings, duration: f32) -> Self {
+    Self {
+      from,
+      to,
+      duration,
+      ease: EaseFunction::SmoothStep,
+      elapsed: 0.0,
+    }
+  }
+
+  /// Blend between two presets over `duration` seconds.
+  pub fn from_presets(from: &WaterPreset, to: &WaterPreset, duration: f32) -> Self {
+    Self::new(from.settings.clone(), to.settings.clone(), duration)
+  }
+
+  /// Set the easing curve.
+  pub fn with_ease(mut self, ease: EaseFunction) -> Self {
+    self.ease = ease;
+    self
+  }
+
+  /// Progress of the blend from 0.0 to 1.0 (before easing).
+  pub fn progress(&self) -> f32 {
+    if self.duration > 0.0 {
+      (self.elapsed / self.duration).clamp(0.0, 1.0)
+    } else {
+      1.0
+    }
+  }
+
+  /// Returns true when the blend is done.
+  pub fn is_finished(&self) -> bool {
+    self.progress() >= 1.0
+  }
+
+  /// The blended settings.
+  pub fn sample(&self) -> WaterSettings {
+    blend_settings(
+      &self.from,
+      &self.to,
+      self.progress(),
+      self.ease,
+      self.duration,
+    )
+  }
+}
+
+/// A keyframe of a `WaterTimeline`.
+#[derive(Clone, Debug, Reflect)]
+pub struct WaterKeyframe {
+  /// Timeline time in seconds.
+  pub time: f32,
+  /// Settings at this keyframe.
+  pub settings: WaterSettings,
+  /// Use the settings of this preset instead, the timeline waits for it to load.
+  pub preset: Option<Handle<WaterPreset>>,
+  /// Easing curve from the previous keyframe to this one.
+  pub ease: EaseFunction,
+}
+
+impl WaterKeyframe {
+  /// Keyframe with `settings` at `time`.
+  pub fn new(time: f32, settings: WaterSettings) -> Self {
+    Self {
+      time,
+      settings,
+      preset: None,
+      ease: EaseFunction::SmoothStep,
+    }
+  }
+
+  /// Keyframe with a preset at `time`.
+  pub fn preset(time: f32, preset: Handle<WaterPreset>) -> Self {
+    Self {
+      time,
+      settings: WaterSettings::default(),
+      preset: Some(preset),
+      ease: EaseFunction::SmoothStep,
+    }
+  }
+
+  /// Set the easing curve from the previous keyframe.
+  pub fn with_ease(mut self, ease: EaseFunction) -> Self {
+    self.ease = ease;
+    self
+  }
+
+  fn settings<'a>(&'a self, presets: &'a Assets<WaterPreset>) -> Option<&'a WaterSettings> {
+    match &self.preset {
+      Some(handle) => presets.get(handle).map(|preset| &preset.settings),
+      None => Some(&self.settings),
+    }
+  }
+}
+
+/// Sequence of water keyframes, for cutscenes and weather changes.
+///
+/// On a water body entity (with a water material) the timeline drives the body's material,
+/// which is then no longer updated from the global `WaterSettings`.  On any other entity
+/// the timeline drives the global `WaterSettings`.
+///
+/// The settings hold the first/last keyframe outside of the timeline.
+#[derive(Component, Clone, Debug, Default, Reflect)]
+#[reflect(Component, Default)]
+pub struct WaterTimeline {
+  /// Keyframes, sorted by time.
+  pub keyframes: Vec<WaterKeyframe>,
+  /// Current timeline time in seconds.
+  pub time: f32,
+  /// Stop advancing the timeline.
+  pub paused: bool,
+  /// Restart at the first keyframe after the last one.
+  pub looping: bool,
+  /// The last keyframe has been applied.
+  done: bool,
+}
+
+impl WaterTimeline {
+  /// Timeline with the given keyframes.
+  pub fn new(keyframes: impl IntoIterator<Item = WaterKeyframe>) -> Self {
+    let mut keyframes: Vec<_> = keyframes.into_iter().collect();
+    keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+    Self {
+      keyframes,
+      ..default()
+    }
+  }
+
+  /// Loop the timeline.
+  pub fn with_looping(mut self, looping: bool) -> Self {
+    self.looping = looping;
+    self
+  }
+
+  /// Time of the last keyframe.
+  pub fn duration(&self) -> f32 {
+    self.keyframes.last().map(|k| k.time).unwrap_or(0.0)
+  }
+
+  /// Returns true after the last keyframe (never for looping timelines).
+  pub fn is_finished(&self) -> bool {
+    !self.looping && self.time >= self.duration()
+  }
+
+  /// The settings at the current time, `None` while the keyframe presets are loading.
+  pub fn sample(&self, presets: &Assets<WaterPreset>) -> Option<WaterSettings> {
+    let (first, last) = (self.keyframes.first()?, self.keyframes.last()?);
+    let duration = self.duration();
+    let time = if self.looping && duration > 0.0 {
+      self.time.rem_euclid(duration)
+    } else {
+      self.time
+    };
+    let next = self.keyframes.partition_point(|k| k.time <= time);
+    if next == 0 {
+      return first.settings(presets).cloned();
+    }
+    if next == self.keyframes.len() {
+      return last.settings(presets).cloned();
+    }
+    let (a, b) = (&self.keyframes[next - 1], &self.keyframes[next]);
+    let span = b.time - a.time;
+    let t = (time - a.time) / span.max(f32::EPSILON);
+    Some(blend_settings(
+      a.settings(presets)?,
+      b.settings(presets)?,
+      t,
+      b.ease,
+      span,
+    ))
+  }
+}
+
+/// Apply the `WaterBlend` to the global `WaterSettings`.
+pub fn update_water_blend(
+  mut commands: Commands,
+  time: Res<Time>,
+  mut blend: ResMut<WaterBlend>,
+  mut settings: ResMut<WaterSettings>,
+) {
+  blend.elapsed += time.delta_secs();
+  *settings = blend.sample();
+  if blend.is_finished() {
+    commands.remove_resource::<WaterBlend>();
+  }
+}
+
+type TimelineQuery = (
+  &'static mut WaterTimeline,
+  Option<&'static MeshMaterial3d<StandardWaterMaterial>>,
+  Option<&'static WaterFlow>,
+  Option<&'static mut WaveDirection>,
+);
+
+/// Play the `WaterTimeline`s.
+pub fn update_water_timelines(
+  time: Res<Time>,
+  presets: Res<Assets<WaterPreset>>,
+  mut settings: ResMut<WaterSettings>,
+  mut materials: ResMut<Assets<StandardWaterMaterial>>,
+  mut timelines: Query<TimelineQuery>,
+) {
+  let dt = time.delta_secs();
+  for (mut timeline, material, flow, mut wave_dir) in timelines.iter_mut() {
+    // Changed by the user, play again.
+    if timeline.is_changed() {
+      timeline.bypass_change_detection().done = false;
+    }
+    if timeline.done {
+      continue;
+    }
+    let Some(timeline_settings) = timeline.sample(&presets) else {
+      continue;
+    };
+    match material {
+      Some(material) => apply_body_settings(
+        &timeline_settings,
+        &mut materials,
+        material,
+        flow,
+        wave_dir.as_deref_mut(),
+      ),
+      None => *settings = timeline_settings,
+    }
+    let timeline = timeline.bypass_change_detection();
+    timeline.done = timeline.is_finished() || timeline.paused;
+    if !timeline.paused {
+      timeline.time += dt;
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use bevy::time::TimeUpdateStrategy;
+
+  use super::*;
+  use crate::WaterQuality;
+
+  #[test]
+  fn timeline_blends_between_keyframes() {
+    let calm = WaterSettings {
+      amplitude: 0.5,
+      height: 0.0,
+      water_quality: WaterQuality::Medium,
+      spawn_tiles: Some(UVec2::new(4, 4)),
+      ..default()
+    };
+    let storm = WaterSettings {
+      amplitude: 3.0,
+      height: 1.0,
+      water_quality: WaterQuality::Ultra,
+      spawn_tiles: Some(UVec2::new(6, 6)),
+      refraction: true,
+      ..default()
+    };
+    let mut app = App::new();
+    app
+      .add_plugins(MinimalPlugins)
+      .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+        250,
+      )))
+      .insert_resource(calm.clone())
+      .init_resource::<Assets<WaterPreset>>()
+      .init_resource::<Assets<StandardWaterMaterial>>()
+      .add_systems(Update, update_water_timelines);
+    app.world_mut().spawn(WaterTimeline::new([
+      WaterKeyframe::new(0.0, calm.clone()),
+      // Overshoots before the end of the blend.
+      WaterKeyframe::new(4.0, storm.clone()).with_ease(EaseFunction::BackOut),
+    ]));
+
+    let mut intermediate = 0;
+    for _ in 0..30 {
+      // The timeline samples before advancing its time.
+      let time = app
+        .world_mut()
+        .query::<&WaterTimeline>()
+        .single(app.world())
+        .unwrap()
+        .time;
+      app.update();
+      let settings = app.world().resource::<WaterSettings>();
+      if time < 4.0 {
+        assert!((calm.amplitude..=storm.amplitude).contains(&settings.amplitude));
+        assert!((calm.height..=storm.height).contains(&settings.height));
+        if settings.amplitude > calm.amplitude && settings.amplitude < storm.amplitude {
+          intermediate += 1;
+        }
+        assert_eq!(settings.water_quality, calm.water_quality, "time {time}");
+        assert_eq!(settings.spawn_tiles, calm.spawn_tiles);
+        assert!(!settings.refraction);
+      }
+    }
+    assert!(intermediate > 3, "{intermediate} intermediate frames");
+    let settings = app.world().resource::<WaterSettings>();
+    assert_eq!(settings.amplitude, storm.amplitude);
+    assert_eq!(settings.water_quality, storm.water_quality);
+    assert_eq!(settings.spawn_tiles, storm.spawn_tiles);
+    assert!(settings.refraction);
+  }
+}
@@ -0,0 +1,212 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{
+  flow::sample_flow_advected, lake::Lake, river::River, wave::get_wave_height_2d, WaterFlow,
+  WaterParam, WaterSphere, WaterSphereSurface, WaterVolume, WaterVolumeSurface,
+};
+
+/// A point on the surface of a water body.
+#[derive(Clone, Copy, Debug)]
+pub struct WaterSurface {
+  /// The water body entity, `None` for the global water tiles.
+  pub body: Option<Entity>,
+  /// Global height of the water surface (including waves).
+  pub height: f32,
+  /// Flow velocity on the X/Z plane in world units per second.
+  pub flow: Vec2,
+}
+
+type LakeQuery = (
+  Entity,
+  &'static Lake,
+  Option<&'static WaterFlow>,
+  &'static GlobalTransform,
+);
+
+type RiverQuery = (
+  Entity,
+  &'static River,
+  &'static WaterFlow,
+  &'static GlobalTransform,
+);
+
+/// A system parameter used to query the water surface of all water bodies
+/// (volumes, rivers, lakes) and the global water tiles.
+#[derive(SystemParam)]
+pub struct WaterBodies<'w, 's> {
+  pub water: WaterParam<'w>,
+  pub rivers: Query<'w, 's, RiverQuery>,
+  pub lakes: Query<'w, 's, LakeQuery>,
+  pub volumes: Query<'w, 's, (&'static WaterVolume, &'static GlobalTransform)>,
+  pub volume_surfaces: Query<'w, 's, (&'static WaterVolumeSurface, &'static GlobalTransform)>,
+  pub spheres: Query<'w, 's, (Entity, &'static WaterSphere, &'static GlobalTransform)>,
+}
+
+impl<'w, 's> WaterBodies<'w, 's> {
+  /// Get the water surface at the given global position.
+  ///
+  /// Water bodies are checked first, then the global water tiles (if spawned).
+  pub fn surface(&self, position: Vec3) -> Option<WaterSurface> {
+    self
+      .volume_surface(position)
+      .or_else(|| self.river_surface(position))
+      .or_else(|| self.lake_surface(position))
+      .or_else(|| {
+        self.water.settings.spawn_tiles.map(|_| WaterSurface {
+          body: None,
+          height: self.water.wave_height(position),
+          flow: self.water.flow_velocity(position),
+        })
+      })
+  }
+
+  /// Returns the lake entity containing the given global position.
+  pub fn lake_at(&self, position: Vec3) -> Option<Entity> {
+    self
+      .lakes
+      .iter()
+      .find(|(_, lake, _, transform)| {
+        let local = transform.affine().inverse().transform_point3(position);
+        lake.contains(local.xz())
+      })
+      .map(|(entity, _, _, _)| entity)
+  }
+
+  /// Depth of the given global position below the water surface (negative above the surface).
+  ///
+  /// Returns `None` if the position isn't in or over a water body.
+  pub fn depth(&self, position: Vec3) -> Option<f32> {
+    self
+      .surface(position)
+      .map(|surface| surface.height - position.y)
+  }
+
+  /// Returns true if the given global position is under water.
+  pub fn is_submerged(&self, position: Vec3) -> bool {
+    self.depth(position).is_some_and(|depth| depth > 0.0)
+  }
+
+  /// Get the surface of the closest spherical water body (planet ocean) to the given global position.
+  ///
+  /// The surface is along the radial direction from the planet center through `position`.
+  pub fn sphere_surface(&self, position: Vec3) -> Option<WaterSphereSurface> {
+    self
+      .spheres
+      .iter()
+      .map(|(entity, sphere, transform)| {
+        let center = transform.translation();
+        let radius = sphere.global_radius(transform);
+        let height = self.water.sphere_wave_height(center, radius, position);
+        let up = (position - center).normalize_or(Vec3::Y);
+        WaterSphereSurface {
+          body: entity,
+          point: center + up * height,
+          up,
+          depth: height - position.distance(center),
+        }
+      })
+      .min_by(|a, b| a.depth.abs().total_cmp(&b.depth.abs()))
+  }
+
+  /// Calculates the height of the water surface at the given global position.
+  pub fn wave_height(&self, position: Vec3) -> Option<f32> {
+    self.surface(position).map(|surface| surface.height)
+  }
+
+  /// Calculates the normal of the water surface at the given global position.
+  ///
+  /// Uses the same forward difference as `WaterParam::wave_normal`.
+  pub fn surface_normal(&self, position: Vec3) -> Option<Vec3> {
+    let h = self.wave_height(position)?;
+    let h_dx = self.wave_height(position + Vec3::X).unwrap_or(h);
+    let h_dz = self.wave_height(position + Vec3::Z).unwrap_or(h);
+    Some(Vec3::new(h - h_dx, 1., h - h_dz).normalize())
+  }
+
+  /// Calculates the flow velocity at the given global position.
+  pub fn flow_velocity(&self, position: Vec3) -> Vec2 {
+    self
+      .surface(position)
+      .map(|surface| surface.flow)
+      .unwrap_or_default()
+  }
+
+  /// Surface of the water volume containing the given global position (or above its water).
+  fn volume_surface(&self, position: Vec3) -> Option<WaterSurface> {
+    let settings = &self.water.settings;
+    self
+      .volume_surfaces
+      .iter()
+      .find_map(|(surface, surface_transform)| {
+        let (volume, transform) = self.volumes.get(surface.volume).ok()?;
+        let inverse = transform.affine().inverse();
+        let local = inverse.transform_point3(position);
+        let level = inverse.transform_point3(surface_transform.translation()).y;
+        // Inside the hull, below the water level or above the surface.
+        let inside = if local.y < level {
+          volume.contains(local)
+        } else {
+          volume.contains(Vec3::new(local.x, level, local.z))
+        };
+        if !inside {
+          return None;
+        }
+        let wave = if settings.amplitude != 0.0 {
+          (self.water.wave_height(position) - self.water.height()) / settings.amplitude
+        } else {
+          0.0
+        };
+        let level_point = transform.transform_point(Vec3::new(local.x, level, local.z));
+        Some(WaterSurface {
+          body: Some(surface.volume),
+          height: level_point.y + volume.amplitude * wave,
+          flow: Vec2::ZERO,
+        })
+      })
+  }
+
+  fn river_surface(&self, position: Vec3) -> Option<WaterSurface> {
+    let time = self.water.time.elapsed_secs_wrapped();
+    let wave_time = self.water.wave_state.wave_time(time);
+    let wave_length = self.water.settings.wave_length.max(0.001);
+    let quality = self.water.settings.water_quality.into();
+    let amplitude = self.water.settings.amplitude;
+    self
+      .rivers
+      .iter()
+      .find_map(|(entity, river, flow, transform)| {
+        let local = transform.affine().inverse().transform_point3(position);
+        let projection = river.project(local).filter(|p| p.is_inside())?;
+        // Match the river shader: waves travel along the `y` wave coordinate.
+        let speed = projection.point.speed;
+        let wave = sample_flow_advected(
+          time,
+          Vec2::new(0.0, speed),
+          flow.period,
+          projection.coord(),
+          |p| get_wave_height_2d(wave_time, p / wave_length, Vec2::Y, quality),
+        );
+        let surface = projection.point.position + projection.point.right * projection.lateral;
+        let height = transform.transform_point(surface).y + amplitude * wave;
+        let direction = transform.affine().transform_vector3(projection.direction());
+        Some(WaterSurface {
+          body: Some(entity),
+          height,
+          flow: direction.xz().normalize_or_zero() * speed,
+        })
+      })
+  }
+
+  fn lake_surface(&self, position: Vec3) -> Option<WaterSurface> {
+    let entity = self.lake_at(position)?;
+    let (_, _, flow, transform) = self.lakes.get(entity).ok()?;
+    // Lakes share the world-aligned waves of the global water, with their own flow (if any).
+    let flow = flow.unwrap_or(&self.water.settings.flow);
+    let wave = self.water.wave_height_with_flow(flow, position) - self.water.height();
+    Some(WaterSurface {
+      body: Some(entity),
+      height: transform.translation().y + wave,
+      flow: self.water.flow_velocity_with_flow(flow, position),
+    })
+  }
+}
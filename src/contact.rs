@@ -0,0 +1,337 @@
+use bevy::prelude::*;
+
+use crate::WaterBodies;
+
+/// Track when an entity enters, leaves or goes under the water.
+///
+/// Sends `WaterEntered`, `WaterExited` and `Submerged` messages, so particle, audio
+/// and damage systems can react to the same contacts.  Works with all water bodies
+/// (see `WaterBodies`), including spherical water.
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[reflect(Component, Default)]
+#[require(Transform)]
+pub struct WaterContact {
+  /// Radius of the entity.  It touches the water when the surface is within this distance.
+  pub radius: f32,
+  state: WaterContactState,
+  #[entities]
+  body: Option<Entity>,
+  depth: f32,
+  velocity: Vec3,
+  last_position: Option<Vec3>,
+}
+
+/// How far an entity with `WaterContact` is in the water.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+pub enum WaterContactState {
+  /// Out of the water.
+  #[default]
+  Dry,
+  /// Touching the water surface.
+  Touching,
+  /// Completely under water.
+  Submerged,
+}
+
+impl Default for WaterContact {
+  fn default() -> Self {
+    Self::new(0.5)
+  }
+}
+
+impl WaterContact {
+  /// Track an entity with the given radius.
+  pub fn new(radius: f32) -> Self {
+    Self {
+      radius,
+      state: WaterContactState::Dry,
+      body: None,
+      depth: 0.0,
+      velocity: Vec3::ZERO,
+      last_position: None,
+    }
+  }
+
+  /// How far the entity is in the water.
+  pub fn state(&self) -> WaterContactState {
+    self.state
+  }
+
+  /// The water body the entity is in or was last in, `None` for the global water tiles.
+  pub fn body(&self) -> Option<Entity> {
+    self.body
+  }
+
+  /// Depth of the entity's center below the water surface (negative above the surface).
+  ///
+  /// `f32::NEG_INFINITY` if there's no water above or below the entity.
+  pub fn depth(&self) -> f32 {
+    self.depth
+  }
+
+  /// Returns true if the entity is touching or under the water.
+  pub fn is_in_water(&self) -> bool {
+    self.state != WaterContactState::Dry
+  }
+
+  /// Velocity of the entity measured from its `GlobalTransform`.
+  pub fn velocity(&self) -> Vec3 {
+    self.velocity
+  }
+}
+
+/// Sent when an entity with `WaterContact` hits the water surface.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct WaterEntered {
+  pub entity: Entity,
+  /// The water body, `None` for the global water tiles.
+  pub body: Option<Entity>,
+  /// Global position of the impact on the water surface.
+  pub position: Vec3,
+  /// Velocity of the entity at impact.
+  pub velocity: Vec3,
+  /// Normal of the water surface at the impact.
+  pub normal: Vec3,
+}
+
+impl WaterEntered {
+  /// Speed into the water surface.
+  pub fn impact_speed(&self) -> f32 {
+    (-self.velocity.dot(self.normal)).max(0.0)
+  }
+}
+
+/// Sent when an entity with `WaterContact` leaves the water.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct WaterExited {
+  pub entity: Entity,
+  /// The water body, `None` for the global water tiles.
+  pub body: Option<Entity>,
+  /// Global position on the water surface where the entity left the water.
+  pub position: Vec3,
+  /// Velocity of the entity when leaving the water.
+  pub velocity: Vec3,
+  /// Normal of the water surface.
+  pub normal: Vec3,
+}
+
+/// Sent when an entity with `WaterContact` goes completely under water.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct Submerged {
+  pub entity: Entity,
+  /// The water body, `None` for the global water tiles.
+  pub body: Option<Entity>,
+  /// Global position of the entity.
+  pub position: Vec3,
+  /// Velocity of the entity.
+  pub velocity: Vec3,
+  /// Depth of the entity's center below the water surface.
+  pub depth: f32,
+}
+
+/// Point on the water surface above or below a position.
+struct ContactSurface {
+  body: Option<Entity>,
+  point: Vec3,
+  normal: Vec3,
+  depth: f32,
+}
+
+fn contact_surface(bodies: &WaterBodies, position: Vec3) -> Option<ContactSurface> {
+  if let Some(surface) = bodies.surface(position) {
+    return Some(ContactSurface {
+      body: surface.body,
+      point: Vec3::new(position.x, surface.height, position.z),
+      normal: bodies.surface_normal(position).unwrap_or(Vec3::Y),
+      depth: surface.height - position.y,
+    });
+  }
+  bodies
+    .sphere_surface(position)
+    .map(|surface| ContactSurface {
+      body: Some(surface.body),
+      point: surface.point,
+      normal: surface.up,
+      depth: surface.depth,
+    })
+}
+
+/// Update `WaterContact` entities and send the water contact messages.
+pub fn update_water_contacts(
+  time: Res<Time>,
+  bodies: WaterBodies,
+  mut contacts: Query<(Entity, &mut WaterContact, &GlobalTransform)>,
+  mut entered: MessageWriter<WaterEntered>,
+  mut exited: MessageWriter<WaterExited>,
+  mut submerged: MessageWriter<Submerged>,
+) {
+  let dt = time.delta_secs();
+  for (entity, mut contact, transform) in contacts.iter_mut() {
+    let position = transform.translation();
+    let last = contact.last_position.replace(position);
+    if let Some(last) = last {
+      if dt > 0.0 {
+        contact.velocity = (position - last) / dt;
+      }
+    }
+
+    let surface = contact_surface(&bodies, position);
+    let depth = surface
+      .as_ref()
+      .map(|surface| surface.depth)
+      .unwrap_or(f32::NEG_INFINITY);
+    let state = if depth > contact.radius {
+      WaterContactState::Submerged
+    } else if depth > -contact.radius {
+      WaterContactState::Touching
+    } else {
+      WaterContactState::Dry
+    };
+    let previous = contact.state;
+    let previous_body = contact.body;
+    contact.depth = depth;
+    contact.state = state;
+    if let Some(surface) = surface.as_ref().filter(|_| state != WaterContactState::Dry) {
+      contact.body = surface.body;
+    }
+
+    // Don't send messages for entities spawned in the water.
+    if last.is_none() || state == previous {
+      continue;
+    }
+    let velocity = contact.velocity;
+    let body = surface.as_ref().and_then(|surface| surface.body);
+    match (previous, surface) {
+      (WaterContactState::Dry, Some(surface)) => {
+        entered.write(WaterEntered {
+          entity,
+          body: surface.body,
+          position: surface.point,
+          velocity,
+          normal: surface.normal,
+        });
+      }
+      (_, Some(surface)) if state == WaterContactState::Dry => {
+        exited.write(WaterExited {
+          entity,
+          body: surface.body,
+          position: surface.point,
+          velocity,
+          normal: surface.normal,
+        });
+      }
+      (_, None) => {
+        // Left the area of the water body.
+        exited.write(WaterExited {
+          entity,
+          body: previous_body,
+          position,
+          velocity,
+          normal: Vec3::Y,
+        });
+      }
+      _ => {}
+    }
+    if state == WaterContactState::Submerged {
+      submerged.write(Submerged {
+        entity,
+        body,
+        position,
+        velocity,
+        depth,
+      });
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use bevy::time::TimeUpdateStrategy;
+
+  use super::*;
+  use crate::{GlobalWaveState, WaterSettings, WaterVolume, WaterVolumeSurface};
+
+  #[derive(Resource, Default)]
+  struct Log(Vec<(&'static str, Option<Entity>)>);
+
+  fn log_messages(
+    mut log: ResMut<Log>,
+    mut entered: MessageReader<WaterEntered>,
+    mut submerged: MessageReader<Submerged>,
+    mut exited: MessageReader<WaterExited>,
+  ) {
+    log.0.extend(entered.read().map(|m| ("entered", m.body)));
+    log
+      .0
+      .extend(submerged.read().map(|m| ("submerged", m.body)));
+    log.0.extend(exited.read().map(|m| ("exited", m.body)));
+  }
+
+  #[test]
+  fn contact_messages_in_order() {
+    let mut app = App::new();
+    app
+      .add_plugins(MinimalPlugins)
+      .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+        100,
+      )))
+      .insert_resource(WaterSettings {
+        spawn_tiles: None,
+        amplitude: 0.0,
+        ..default()
+      })
+      .init_resource::<GlobalWaveState>()
+      .init_resource::<Log>()
+      .add_message::<WaterEntered>()
+      .add_message::<WaterExited>()
+      .add_message::<Submerged>()
+      .add_systems(Update, (update_water_contacts, log_messages).chain());
+    // A tank from -1.0 to 1.0, filled up to 0.0.
+    let volume = app
+      .world_mut()
+      .spawn((WaterVolume::cuboid(Vec3::ONE), GlobalTransform::IDENTITY))
+      .id();
+    app
+      .world_mut()
+      .spawn((WaterVolumeSurface { volume }, GlobalTransform::IDENTITY));
+    let entity = app
+      .world_mut()
+      .spawn((
+        WaterContact::new(0.1),
+        GlobalTransform::from_xyz(0.0, 2.0, 0.0),
+      ))
+      .id();
+
+    let mut log = Vec::new();
+    for (position, state) in [
+      (Vec3::new(0.0, 2.0, 0.0), WaterContactState::Dry),
+      (Vec3::new(0.0, 0.0, 0.0), WaterContactState::Touching),
+      (Vec3::new(0.0, -0.5, 0.0), WaterContactState::Submerged),
+      (Vec3::new(0.5, -0.5, 0.0), WaterContactState::Submerged),
+      (Vec3::new(0.5, 0.0, 0.0), WaterContactState::Touching),
+      // Out through the side of the tank.
+      (Vec3::new(3.0, 0.0, 0.0), WaterContactState::Dry),
+    ] {
+      app
+        .world_mut()
+        .entity_mut(entity)
+        .insert(GlobalTransform::from_translation(position));
+      app.update();
+      let contact = app.world().get::<WaterContact>(entity).unwrap();
+      assert_eq!(contact.state(), state, "at {position}");
+      log.append(&mut app.world_mut().resource_mut::<Log>().0);
+    }
+    assert_eq!(
+      log,
+      [
+        ("entered", Some(volume)),
+        ("submerged", Some(volume)),
+        ("exited", Some(volume)),
+      ]
+    );
+    let contact = app.world().get::<WaterContact>(entity).unwrap();
+    assert_eq!(contact.body(), Some(volume));
+  }
+}
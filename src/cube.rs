@@ -0,0 +1,189 @@
+use bevy::{
+  asset::RenderAssetUsages,
+  mesh::{Indices, MeshBuilder, PrimitiveTopology},
+  prelude::*,
+};
+
+use crate::{material::StandardWaterMaterial, WaterSettings};
+
+/// A face of a cube.  The order matches the face index encoded in the water mesh UVs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Reflect)]
+pub enum CubeFace {
+  PosX,
+  NegX,
+  PosY,
+  NegY,
+  PosZ,
+  NegZ,
+}
+
+impl CubeFace {
+  pub const ALL: [CubeFace; 6] = [
+    CubeFace::PosX,
+    CubeFace::NegX,
+    CubeFace::PosY,
+    CubeFace::NegY,
+    CubeFace::PosZ,
+    CubeFace::NegZ,
+  ];
+
+  /// Index of the face in the multi-face UVs.
+  pub fn index(self) -> u32 {
+    self as u32
+  }
+
+  /// Outward normal of the face.
+  pub fn normal(self) -> Vec3 {
+    match self {
+      CubeFace::PosX => Vec3::X,
+      CubeFace::NegX => Vec3::NEG_X,
+      CubeFace::PosY => Vec3::Y,
+      CubeFace::NegY => Vec3::NEG_Y,
+      CubeFace::PosZ => Vec3::Z,
+      CubeFace::NegZ => Vec3::NEG_Z,
+    }
+  }
+
+  /// Point on the unit cube (-1.0 to 1.0) for the face position `st` (-1.0 to 1.0).
+  ///
+  /// Matches `uv_to_cube_coord` in the water shader.
+  pub fn point(self, st: Vec2) -> Vec3 {
+    match self {
+      CubeFace::PosX => Vec3::new(1.0, -st.y, -st.x),
+      CubeFace::NegX => Vec3::new(-1.0, -st.y, st.x),
+      CubeFace::PosY => Vec3::new(st.x, 1.0, st.y),
+      CubeFace::NegY => Vec3::new(st.x, -1.0, -st.y),
+      CubeFace::PosZ => Vec3::new(st.x, -st.y, 1.0),
+      CubeFace::NegZ => Vec3::new(-st.x, -st.y, -1.0),
+    }
+  }
+
+  /// Multi-face UV for the face position `st` (-1.0 to 1.0): `x` is `face * 2 + u`.
+  pub fn uv(self, st: Vec2) -> Vec2 {
+    let uv = st * 0.5 + 0.5;
+    Vec2::new(self.index() as f32 * 2.0 + uv.x, uv.y)
+  }
+}
+
+/// Map multi-face UVs (see `CubeFace::uv`) to the point on a cube with `half_size`.
+///
+/// This is the wave coordinate used by cube water materials (`WaterMaterial::cube`).
+pub fn uv_to_cube_coord(uv: Vec2, half_size: f32) -> Vec3 {
+  let face = (uv.x / 2.0).floor().clamp(0.0, 5.0);
+  let st = Vec2::new(uv.x - face * 2.0, uv.y) * 2.0 - 1.0;
+  CubeFace::ALL[face as usize].point(st) * half_size
+}
+
+/// Builder for seamless cube and cube-sphere water meshes.
+///
+/// The UVs encode the cube face, so the waves are continuous across the face edges
+/// when used with a cube water material (see `CubeWaterMeshBuilder::new_material`).
+///
+/// The mesh is centered on the entity, rotating the entity isn't supported.
+#[derive(Clone, Debug)]
+pub struct CubeWaterMeshBuilder {
+  /// Half size of the cube (or radius of the cube-sphere).
+  pub half_size: f32,
+  /// Number of quads along each face edge.
+  pub subdivisions: u32,
+  /// Faces to build.
+  pub faces: Vec<CubeFace>,
+  /// Project the cube onto a sphere.
+  pub spherical: bool,
+}
+
+impl CubeWaterMeshBuilder {
+  /// A cube with all six faces.
+  pub fn cube(half_size: f32) -> Self {
+    Self {
+      half_size,
+      subdivisions: 32,
+      faces: CubeFace::ALL.to_vec(),
+      spherical: false,
+    }
+  }
+
+  /// A cube-sphere with all six faces.
+  pub fn cube_sphere(radius: f32) -> Self {
+    Self {
+      spherical: true,
+      ..Self::cube(radius)
+    }
+  }
+
+  /// Set the number of quads along each face edge.
+  pub fn subdivisions(mut self, subdivisions: u32) -> Self {
+    self.subdivisions = subdivisions.max(1);
+    self
+  }
+
+  /// Only build some faces (for example the sides of a water column).
+  pub fn faces(mut self, faces: impl IntoIterator<Item = CubeFace>) -> Self {
+    self.faces = faces.into_iter().collect();
+    self
+  }
+
+  /// Create a cube water material for this mesh.
+  pub fn new_material(&self, settings: &WaterSettings) -> StandardWaterMaterial {
+    let mut material = settings.new_material(Vec2::ZERO, Vec2::splat(self.half_size));
+    material.extension.cube = true;
+    material
+  }
+}
+
+impl MeshBuilder for CubeWaterMeshBuilder {
+  fn build(&self) -> Mesh {
+    let n = self.subdivisions.max(1);
+    let cols = n + 1;
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    let mut indices = Vec::new();
+    for face in &self.faces {
+      let base = positions.len() as u32;
+      for y in 0..cols {
+        for x in 0..cols {
+          let st = Vec2::new(x as f32, y as f32) / n as f32 * 2.0 - 1.0;
+          let p = face.point(st);
+          let (position, normal) = if self.spherical {
+            let dir = p.normalize();
+            (dir * self.half_size, dir)
+          } else {
+            (p * self.half_size, face.normal())
+          };
+          positions.push(position.to_array());
+          normals.push(normal.to_array());
+          uvs.push(face.uv(st).to_array());
+        }
+      }
+      // Front faces point away from the center.
+      let step = 2.0 / n as f32;
+      let a = face.point(Vec2::splat(-1.0));
+      let b = face.point(Vec2::new(-1.0 + step, -1.0));
+      let c = face.point(Vec2::new(-1.0, -1.0 + step));
+      let flip = (b - a).cross(c - a).dot(face.normal()) < 0.0;
+      for y in 0..n {
+        for x in 0..n {
+          let a = base + y * cols + x;
+          let b = a + 1;
+          let c = a + cols;
+          let d = c + 1;
+          if flip {
+            indices.extend_from_slice(&[a, c, b, b, c, d]);
+          } else {
+            indices.extend_from_slice(&[a, b, c, b, d, c]);
+          }
+        }
+      }
+    }
+
+    Mesh::new(
+      PrimitiveTopology::TriangleList,
+      RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(indices))
+  }
+}
@@ -0,0 +1,163 @@
+use bevy::prelude::*;
+
+use crate::{
+  fract,
+  material::{StandardWaterMaterial, WaterMaterial},
+  mix,
+};
+
+/// Flow of a water body (rivers, estuaries, currents).
+///
+/// The flow advects the wave pattern and detail normals in the water shader.
+/// Add this component to a water mesh entity to override the flow from `WaterSettings::flow`.
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component, Default)]
+pub struct WaterFlow {
+  /// Constant flow velocity in world units per second (X/Z plane).
+  pub velocity: Vec2,
+  /// Flow map.  The red/green channels encode the flow direction (0.5 = no flow).
+  ///
+  /// Keep the image in the main world (`RenderAssetUsages::MAIN_WORLD`) for CPU sampling.
+  pub flow_map: Option<Handle<Image>>,
+  /// Flow velocity in world units per second for a full flow map value.
+  pub flow_map_scale: f32,
+  /// Area covered by the flow map in wave coordinates.
+  ///
+  /// For water tiles the wave coordinates are the world-space X/Z position.
+  pub flow_map_rect: Rect,
+  /// Period in seconds of the two-phase flow blending.
+  ///
+  /// Longer periods stretch the waves more, shorter periods pulse more.
+  pub period: f32,
+}
+
+impl Default for WaterFlow {
+  fn default() -> Self {
+    Self {
+      velocity: Vec2::ZERO,
+      flow_map: None,
+      flow_map_scale: 1.0,
+      flow_map_rect: Rect::new(-512.0, -512.0, 512.0, 512.0),
+      period: 8.0,
+    }
+  }
+}
+
+impl WaterFlow {
+  /// Create a constant flow.
+  pub fn from_velocity(velocity: Vec2) -> Self {
+    Self {
+      velocity,
+      ..default()
+    }
+  }
+
+  /// Create a flow from a flow map covering `rect` (in wave coordinates).
+  pub fn from_flow_map(flow_map: Handle<Image>, rect: Rect, scale: f32) -> Self {
+    Self {
+      flow_map: Some(flow_map),
+      flow_map_rect: rect,
+      flow_map_scale: scale,
+      ..default()
+    }
+  }
+
+  /// Returns true if the flow moves the water.
+  pub fn is_enabled(&self) -> bool {
+    self.flow_map.is_some() || self.velocity != Vec2::ZERO
+  }
+
+  /// Get the flow velocity at the wave coordinate `p` (the global X/Z position for water tiles).
+  ///
+  /// Matches the flow used by the water shader.  If the flow map isn't loaded
+  /// only the constant `velocity` is returned.
+  pub fn velocity_at(&self, images: Option<&Assets<Image>>, p: Vec2) -> Vec2 {
+    let mut flow = self.velocity;
+    let image = self
+      .flow_map
+      .as_ref()
+      .and_then(|handle| images.and_then(|images| images.get(handle)));
+    if let Some(image) = image {
+      let uv = (p - self.flow_map_rect.min) / self.flow_map_rect.size();
+      flow += sample_flow_map(image, uv) * self.flow_map_scale;
+    }
+    flow
+  }
+
+  /// Copy the flow settings into a `WaterMaterial`.
+  pub fn apply_to(&self, material: &mut WaterMaterial) {
+    material.flow_velocity = self.velocity;
+    material.flow_map = self.flow_map.clone();
+    material.flow_map_scale = self.flow_map_scale;
+    material.flow_map_offset = self.flow_map_rect.min;
+    material.flow_map_size = self.flow_map_rect.size();
+    material.flow_period = self.period;
+  }
+}
+
+/// Bilinear sample of a flow map (clamp to edge), returns the flow direction in -1..1.
+fn sample_flow_map(image: &Image, uv: Vec2) -> Vec2 {
+  let size = image.size();
+  if size.x == 0 || size.y == 0 {
+    return Vec2::ZERO;
+  }
+  let max = size.as_vec2() - 1.0;
+  let texel = (uv * size.as_vec2() - 0.5).clamp(Vec2::ZERO, max);
+  let base = texel.floor();
+  let f = texel - base;
+  let fetch = |x: f32, y: f32| -> Vec2 {
+    let x = x.min(max.x) as u32;
+    let y = y.min(max.y) as u32;
+    image
+      .get_color_at(x, y)
+      .map(|c| {
+        let c = c.to_linear();
+        Vec2::new(c.red, c.green) * 2.0 - 1.0
+      })
+      .unwrap_or(Vec2::ZERO)
+  };
+  let a = fetch(base.x, base.y);
+  let b = fetch(base.x + 1.0, base.y);
+  let c = fetch(base.x, base.y + 1.0);
+  let d = fetch(base.x + 1.0, base.y + 1.0);
+  a.lerp(b, f.x).lerp(c.lerp(d, f.x), f.y)
+}
+
+/// Sample `wave` with the two-phase flow advection used by the water shader.
+///
+/// `time` - Bevy `time.elapsed_secs_wrapped()`.
+/// `flow` - The flow velocity at `p`.
+/// `period` - The flow period.
+pub fn sample_flow_advected(
+  time: f32,
+  flow: Vec2,
+  period: f32,
+  p: Vec2,
+  wave: impl Fn(Vec2) -> f32,
+) -> f32 {
+  if flow == Vec2::ZERO || period <= 0.0 {
+    return wave(p);
+  }
+  let phase_a = fract(time / period);
+  let phase_b = fract(time / period + 0.5);
+  let wave_a = wave(p - flow * phase_a * period);
+  let wave_b = wave(p - flow * phase_b * period);
+  // Each phase is hidden while it resets.
+  mix(wave_a, wave_b, (0.5 - phase_a).abs() * 2.0)
+}
+
+/// Apply `WaterFlow` components to their water materials.
+pub fn apply_water_flow(
+  mut materials: ResMut<Assets<StandardWaterMaterial>>,
+  water_flows: Query<(Ref<WaterFlow>, &MeshMaterial3d<StandardWaterMaterial>)>,
+) {
+  for (flow, material_handle) in water_flows.iter() {
+    // `update_materials` keeps the flow of bodies with their own `WaterFlow`.
+    if !flow.is_changed() {
+      continue;
+    }
+    if let Some(mat) = materials.get_mut(&material_handle.0) {
+      flow.apply_to(&mut mat.extension);
+    }
+  }
+}
@@ -0,0 +1,482 @@
+use std::collections::HashMap;
+
+use bevy::{
+  asset::RenderAssetUsages,
+  light::NotShadowCaster,
+  mesh::{Indices, PrimitiveTopology},
+  prelude::*,
+};
+
+use crate::{material::StandardWaterMaterial, WaterSettings, WaveDirection};
+
+/// Maximum number of subdivisions along each triangle edge.
+const MAX_SUBDIVISIONS: u32 = 64;
+
+/// A lake, pond or pool water body bounded by a polygon.  Spawned by `LakeBuilder::spawn`.
+///
+/// The polygons are on the X/Z plane in the lake entity's local space.
+/// The material uses world X/Z wave coordinates (`WaterMaterial::world_coords`), so the waves,
+/// wakes and ripples line up with the global water tiles, also when the lake is moved, rotated
+/// or scaled.
+#[derive(Component, Clone, Debug, Default, Reflect)]
+#[reflect(Component, Default)]
+pub struct Lake {
+  outline: Vec<Vec2>,
+  holes: Vec<Vec<Vec2>>,
+  bounds: Rect,
+}
+
+impl Lake {
+  /// The shoreline polygon.
+  pub fn outline(&self) -> &[Vec2] {
+    &self.outline
+  }
+
+  /// Islands inside the lake.
+  pub fn holes(&self) -> &[Vec<Vec2>] {
+    &self.holes
+  }
+
+  /// Bounds of the outline.
+  pub fn bounds(&self) -> Rect {
+    self.bounds
+  }
+
+  /// Returns true if the local X/Z position `p` is over the lake's water surface.
+  pub fn contains(&self, p: Vec2) -> bool {
+    self.bounds.contains(p)
+      && polygon_contains(&self.outline, p)
+      && !self.holes.iter().any(|hole| polygon_contains(hole, p))
+  }
+}
+
+/// Builder for polygon-bounded water bodies (lakes, ponds, pools).
+///
+/// ```ignore
+/// LakeBuilder::new(shoreline)
+///   .hole(island)
+///   .height(2.0)
+///   .spawn(&mut commands, &mut meshes, &mut materials, &settings);
+/// ```
+#[derive(Clone, Debug)]
+pub struct LakeBuilder {
+  /// The shoreline polygon (X/Z positions in the lake entity's local space).  Either winding order.
+  pub outline: Vec<Vec2>,
+  /// Islands inside the lake.
+  pub holes: Vec<Vec<Vec2>>,
+  /// Height of the water surface.
+  pub height: f32,
+  /// Maximum length of a triangle edge.  Smaller values give smoother waves near the shore.
+  pub resolution: f32,
+}
+
+impl LakeBuilder {
+  /// Create a lake from a shoreline polygon.
+  pub fn new(outline: impl IntoIterator<Item = Vec2>) -> Self {
+    Self {
+      outline: outline.into_iter().collect(),
+      holes: Vec::new(),
+      height: 0.0,
+      resolution: 8.0,
+    }
+  }
+
+  /// Create a rectangular pool.
+  pub fn from_rect(rect: Rect) -> Self {
+    Self::new([
+      rect.min,
+      Vec2::new(rect.max.x, rect.min.y),
+      rect.max,
+      Vec2::new(rect.min.x, rect.max.y),
+    ])
+  }
+
+  /// Add an island.
+  pub fn hole(mut self, hole: impl IntoIterator<Item = Vec2>) -> Self {
+    self.holes.push(hole.into_iter().collect());
+    self
+  }
+
+  /// Set the height of the water surface.
+  pub fn height(mut self, height: f32) -> Self {
+    self.height = height;
+    self
+  }
+
+  /// Set the maximum length of a triangle edge.
+  pub fn resolution(mut self, resolution: f32) -> Self {
+    self.resolution = resolution;
+    self
+  }
+
+  /// Build the lake polygon.
+  pub fn build_lake(&self) -> Lake {
+    let bounds = self
+      .outline
+      .iter()
+      .fold(Rect::EMPTY, |rect, p| rect.union_point(*p));
+    Lake {
+      outline: self.outline.clone(),
+      holes: self.holes.clone(),
+      bounds,
+    }
+  }
+
+  /// Build the water mesh.  UVs go from 0.0 to 1.0 over the bounds of the outline.
+  ///
+  /// Returns `None` if the polygon can't be triangulated.
+  pub fn build_mesh(&self, lake: &Lake) -> Option<Mesh> {
+    let (points, triangles) = triangulate(&lake.outline, &lake.holes)?;
+
+    // Subdivide every triangle edge into the same number of segments,
+    // so neighbouring triangles share their edge vertices.
+    let longest = triangles
+      .iter()
+      .flat_map(|t| {
+        let [a, b, c] = t.map(|i| points[i]);
+        [a.distance(b), b.distance(c), c.distance(a)]
+      })
+      .fold(0.0_f32, f32::max);
+    let n = (longest / self.resolution.max(0.001))
+      .ceil()
+      .clamp(1.0, MAX_SUBDIVISIONS as f32) as u32;
+
+    let bounds = lake.bounds;
+    let size = bounds.size().max(Vec2::splat(f32::EPSILON));
+    let mut positions = Vec::new();
+    let mut uvs = Vec::new();
+    let mut lookup = HashMap::new();
+    let mut vertex = |p: Vec2| -> u32 {
+      *lookup
+        .entry((p.x.to_bits(), p.y.to_bits()))
+        .or_insert_with(|| {
+          positions.push([p.x, 0.0, p.y]);
+          uvs.push(((p - bounds.min) / size).to_array());
+          positions.len() as u32 - 1
+        })
+    };
+
+    let mut indices = Vec::new();
+    let inv_n = 1.0 / n as f32;
+    for t in &triangles {
+      let [a, b, c] = t.map(|i| points[i]);
+      let point = |i: u32, j: u32| -> Vec2 {
+        let k = n - i - j;
+        (a * i as f32 + b * j as f32 + c * k as f32) * inv_n
+      };
+      for i in 0..n {
+        for j in 0..(n - i) {
+          let p0 = vertex(point(i + 1, j));
+          let p1 = vertex(point(i, j + 1));
+          let p2 = vertex(point(i, j));
+          indices.extend_from_slice(&[p0, p1, p2]);
+          if i + j + 1 < n {
+            let p3 = vertex(point(i + 1, j + 1));
+            indices.extend_from_slice(&[p0, p3, p1]);
+          }
+        }
+      }
+    }
+    let normals = vec![[0.0, 1.0, 0.0]; positions.len()];
+
+    Some(
+      Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+      )
+      .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+      .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+      .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+      .with_inserted_indices(Indices::U32(indices)),
+    )
+  }
+
+  /// Spawn the lake water body with a water material from `settings`.
+  ///
+  /// Returns `None` if the polygon can't be triangulated.
+  pub fn spawn(
+    &self,
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardWaterMaterial>,
+    settings: &WaterSettings,
+  ) -> Option<Entity> {
+    let lake = self.build_lake();
+    let mesh = meshes.add(self.build_mesh(&lake)?);
+    let material = settings.new_grid_material();
+
+    Some(
+      commands
+        .spawn((
+          Name::new("Lake"),
+          lake,
+          Mesh3d(mesh),
+          MeshMaterial3d(materials.add(material)),
+          WaveDirection::with_duration(
+            settings.wave_direction,
+            settings.wave_direction_blend_duration,
+          ),
+          Transform::from_xyz(0.0, self.height, 0.0),
+          Visibility::default(),
+          NotShadowCaster,
+        ))
+        .id(),
+    )
+  }
+}
+
+/// Even-odd point in polygon test.
+fn polygon_contains(polygon: &[Vec2], p: Vec2) -> bool {
+  let mut inside = false;
+  let mut j = polygon.len().wrapping_sub(1);
+  for (i, a) in polygon.iter().enumerate() {
+    let b = polygon[j];
+    if (a.y > p.y) != (b.y > p.y) && p.x < (b.x - a.x) * (p.y - a.y) / (b.y - a.y) + a.x {
+      inside = !inside;
+    }
+    j = i;
+  }
+  inside
+}
+
+fn signed_area(polygon: &[Vec2]) -> f32 {
+  let mut area = 0.0;
+  for (i, a) in polygon.iter().enumerate() {
+    let b = polygon[(i + 1) % polygon.len()];
+    area += a.perp_dot(b);
+  }
+  area / 2.0
+}
+
+/// Cross product of `b - a` and `c - a`, positive if `a`, `b`, `c` are counter-clockwise.
+fn orient(a: Vec2, b: Vec2, c: Vec2) -> f32 {
+  (b - a).perp_dot(c - a)
+}
+
+fn in_triangle(a: Vec2, b: Vec2, c: Vec2, p: Vec2) -> bool {
+  orient(a, b, p) >= 0.0 && orient(b, c, p) >= 0.0 && orient(c, a, p) >= 0.0
+}
+
+/// Points and triangles (indices into the points).
+type Triangulation = (Vec<Vec2>, Vec<[usize; 3]>);
+
+/// Triangulate a polygon with holes by ear clipping.
+///
+/// Holes are joined to the outline with bridge edges first.
+/// Returns the points and triangles with an upward (+Y) facing winding on the X/Z plane.
+fn triangulate(outline: &[Vec2], holes: &[Vec<Vec2>]) -> Option<Triangulation> {
+  if outline.len() < 3 {
+    return None;
+  }
+  let mut points = outline.to_vec();
+  // Outline counter-clockwise, holes clockwise.
+  let mut ring: Vec<usize> = (0..points.len()).collect();
+  if signed_area(outline) < 0.0 {
+    ring.reverse();
+  }
+
+  let mut hole_rings: Vec<Vec<usize>> = holes
+    .iter()
+    .filter(|hole| hole.len() >= 3)
+    .map(|hole| {
+      let start = points.len();
+      points.extend_from_slice(hole);
+      let mut hole_ring: Vec<usize> = (start..points.len()).collect();
+      if signed_area(hole) > 0.0 {
+        hole_ring.reverse();
+      }
+      hole_ring
+    })
+    .collect();
+
+  // Bridge the holes from right to left, so every bridge reaches the outline.
+  let max_x = |hole: &Vec<usize>| hole.iter().map(|i| points[*i].x).fold(f32::MIN, f32::max);
+  hole_rings.sort_by(|a, b| max_x(b).total_cmp(&max_x(a)));
+  for hole in hole_rings {
+    ring = bridge_hole(&points, ring, &hole)?;
+  }
+
+  let mut triangles = Vec::with_capacity(ring.len().saturating_sub(2));
+  let mut guard = 0;
+  while ring.len() > 3 {
+    let count = ring.len();
+    let ear = (0..count).find(|&i| {
+      let a = points[ring[(i + count - 1) % count]];
+      let b = points[ring[i]];
+      let c = points[ring[(i + 1) % count]];
+      if orient(a, b, c) <= 0.0 {
+        return false;
+      }
+      // No other vertex inside the ear (bridge vertices are duplicated, so compare positions).
+      !ring.iter().any(|&j| {
+        let p = points[j];
+        p != a && p != b && p != c && in_triangle(a, b, c, p)
+      })
+    });
+    let i = match ear {
+      Some(i) => i,
+      // Degenerate input, clip a vertex anyway so the loop ends.
+      None => {
+        guard += 1;
+        if guard > count {
+          return None;
+        }
+        0
+      }
+    };
+    let (a, b, c) = (
+      ring[(i + count - 1) % count],
+      ring[i],
+      ring[(i + 1) % count],
+    );
+    if orient(points[a], points[b], points[c]) > 0.0 {
+      triangles.push([a, c, b]);
+    }
+    ring.remove(i);
+  }
+  if ring.len() == 3 && orient(points[ring[0]], points[ring[1]], points[ring[2]]) > 0.0 {
+    triangles.push([ring[0], ring[2], ring[1]]);
+  }
+  Some((points, triangles))
+}
+
+/// Join a (clockwise) hole into the (counter-clockwise) ring with a bridge edge.
+fn bridge_hole(points: &[Vec2], ring: Vec<usize>, hole: &[usize]) -> Option<Vec<usize>> {
+  // Right-most vertex of the hole.
+  let (hole_start, &m_idx) = hole
+    .iter()
+    .enumerate()
+    .max_by(|a, b| points[*a.1].x.total_cmp(&points[*b.1].x))?;
+  let m = points[m_idx];
+
+  // Cast a ray to +X and find the closest edge of the ring it hits.
+  let count = ring.len();
+  let mut hit: Option<(f32, usize)> = None;
+  for i in 0..count {
+    let a = points[ring[i]];
+    let b = points[ring[(i + 1) % count]];
+    if (a.y > m.y) == (b.y > m.y) || a.y == b.y {
+      continue;
+    }
+    let x = a.x + (m.y - a.y) * (b.x - a.x) / (b.y - a.y);
+    if x < m.x || hit.is_some_and(|(hx, _)| x >= hx) {
+      continue;
+    }
+    // Candidate: the edge end point furthest to the right.
+    let candidate = if a.x > b.x { i } else { (i + 1) % count };
+    hit = Some((x, candidate));
+  }
+  let (hit_x, mut best) = hit?;
+
+  // A reflex vertex inside the triangle (M, hit, candidate) would block the bridge,
+  // pick the one with the smallest angle to the ray instead.
+  let hit_point = Vec2::new(hit_x, m.y);
+  let p = points[ring[best]];
+  let (t0, t1, t2) = if p.y < m.y {
+    (m, p, hit_point)
+  } else {
+    (m, hit_point, p)
+  };
+  let mut best_angle = f32::MAX;
+  for i in 0..count {
+    let v = points[ring[i]];
+    if i == best || v == m {
+      continue;
+    }
+    let prev = points[ring[(i + count - 1) % count]];
+    let next = points[ring[(i + 1) % count]];
+    let reflex = orient(prev, v, next) <= 0.0;
+    if reflex && in_triangle(t0, t1, t2, v) {
+      let d = v - m;
+      let angle = d.y.abs().atan2(d.x);
+      if angle < best_angle {
+        best_angle = angle;
+        best = i;
+      }
+    }
+  }
+
+  // ring[..=best], hole (starting at M), M, ring[best], ring[best + 1..]
+  let mut merged = Vec::with_capacity(count + hole.len() + 2);
+  merged.extend_from_slice(&ring[..=best]);
+  merged.extend(hole[hole_start..].iter().chain(&hole[..hole_start]));
+  merged.push(m_idx);
+  merged.push(ring[best]);
+  merged.extend_from_slice(&ring[best + 1..]);
+  Some(merged)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn square(min: f32, max: f32) -> Vec<Vec2> {
+    vec![
+      Vec2::new(min, min),
+      Vec2::new(max, min),
+      Vec2::new(max, max),
+      Vec2::new(min, max),
+    ]
+  }
+
+  /// Triangle count and total area; asserts all triangles have the same (upward) winding.
+  fn triangulated(outline: &[Vec2], holes: &[Vec<Vec2>]) -> (usize, f32) {
+    let (points, triangles) = triangulate(outline, holes).expect("triangulate");
+    let mut area = 0.0;
+    for t in &triangles {
+      let [a, b, c] = t.map(|i| points[i]);
+      let o = orient(a, b, c);
+      assert!(o < 0.0, "winding of {t:?}");
+      area += -o / 2.0;
+    }
+    (triangles.len(), area)
+  }
+
+  #[test]
+  fn triangulate_with_hole() {
+    // Counter-clockwise hole, gets reversed.
+    let (count, area) = triangulated(&square(0.0, 10.0), &[square(4.0, 6.0)]);
+    // n + 2 * holes - 2
+    assert_eq!(count, 8);
+    assert!((area - 96.0).abs() < 1e-3, "area {area}");
+  }
+
+  #[test]
+  fn triangulate_clockwise() {
+    let mut outline = square(0.0, 10.0);
+    outline.reverse();
+    let mut hole = square(4.0, 6.0);
+    hole.reverse();
+    let (count, area) = triangulated(&outline, &[hole]);
+    assert_eq!(count, 8);
+    assert!((area - 96.0).abs() < 1e-3, "area {area}");
+  }
+
+  #[test]
+  fn triangulate_degenerate_vertices() {
+    // Collinear and duplicate vertices on the bottom edge.
+    let outline = [
+      Vec2::new(0.0, 0.0),
+      Vec2::new(5.0, 0.0),
+      Vec2::new(10.0, 0.0),
+      Vec2::new(10.0, 0.0),
+      Vec2::new(10.0, 10.0),
+      Vec2::new(0.0, 10.0),
+    ];
+    let (_, area) = triangulated(&outline, &[]);
+    assert!((area - 100.0).abs() < 1e-3, "area {area}");
+    assert!(triangulate(&outline[..2], &[]).is_none());
+  }
+
+  #[test]
+  fn lake_excludes_island() {
+    let lake = LakeBuilder::new(square(0.0, 10.0))
+      .hole(square(4.0, 6.0))
+      .build_lake();
+    assert!(lake.contains(Vec2::new(1.0, 1.0)));
+    assert!(lake.contains(Vec2::new(7.0, 5.0)));
+    assert!(!lake.contains(Vec2::new(5.0, 5.0)));
+    assert!(!lake.contains(Vec2::new(-1.0, 5.0)));
+    assert!(!lake.contains(Vec2::new(11.0, 5.0)));
+  }
+}
@@ -9,5 +9,53 @@ pub use image_utils::*;
 mod wave;
 pub use wave::*;
 
+mod flow;
+pub use flow::*;
+
 mod param;
 pub use param::WaterParam;
+
+mod river;
+pub use river::*;
+
+mod lake;
+pub use lake::*;
+
+mod volume;
+pub use volume::*;
+
+mod sphere;
+pub use sphere::*;
+
+mod cube;
+pub use cube::*;
+
+mod ripple;
+pub use ripple::*;
+
+mod wake;
+pub use wake::*;
+
+mod body;
+pub use body::*;
+
+mod contact;
+pub use contact::*;
+
+mod splash;
+pub use splash::*;
+
+mod wind;
+pub use wind::*;
+
+mod tide;
+pub use tide::*;
+
+mod preset;
+pub use preset::*;
+
+mod blend;
+pub use blend::*;
+
+mod snapshot;
+pub use snapshot::*;
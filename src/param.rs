@@ -1,8 +1,10 @@
 use bevy::{ecs::system::SystemParam, math::Vec3Swizzles, prelude::*};
 
 use crate::{
+  flow::sample_flow_advected,
   water::{GlobalWaveState, WaterQuality, WaterSettings},
   wave::{get_wave_height_2d, sample_directional_wave_blended},
+  Tide, WaterFlow, WaterRipples, WaterWakes,
 };
 
 /// A system parameter used to calculate wave height and point based on global WaterSettings and Time resources.
@@ -11,9 +13,26 @@ pub struct WaterParam<'w> {
   pub settings: Res<'w, WaterSettings>,
   pub time: Res<'w, Time>,
   pub wave_state: Res<'w, GlobalWaveState>,
+  /// Used to sample the flow map.
+  pub images: Option<Res<'w, Assets<Image>>>,
+  /// Interactive ripples (if enabled).
+  pub ripples: Option<Res<'w, WaterRipples>>,
+  /// Wakes of moving boats.
+  pub wakes: Option<Res<'w, WaterWakes>>,
+  /// Tide driving the water height (if enabled).
+  pub tide: Option<Res<'w, Tide>>,
 }
 
 impl<'w> WaterParam<'w> {
+  /// Height of the calm water, from the `Tide` resource or `WaterSettings::height`.
+  pub fn height(&self) -> f32 {
+    self
+      .tide
+      .as_ref()
+      .map(|tide| tide.height())
+      .unwrap_or(self.settings.height)
+  }
+
   /// Calculates the height of the waves at the given position.
   ///
   /// # Arguments
@@ -24,11 +43,130 @@ impl<'w> WaterParam<'w> {
   ///
   /// The height of the waves at the given global position.
   pub fn wave_height(&self, position: Vec3) -> f32 {
+    self.wave_height_with_flow(&self.settings.flow, position)
+  }
+
+  /// Calculates the height of the waves at the given global position for a water body with its own `WaterFlow`.
+  pub fn wave_height_with_flow(&self, flow: &WaterFlow, position: Vec3) -> f32 {
+    self.height() + self.flat_wave_offset(flow, position.xz(), None)
+  }
+
+  /// Calculates the height of the waves at the given global position, without the wake of `entity`.
+  ///
+  /// Use it for the buoyancy of a boat with a `Wake`, so it rides the other boats' wakes and not its own.
+  pub fn wave_height_excluding(&self, position: Vec3, entity: Entity) -> f32 {
+    self.height() + self.flat_wave_offset(&self.settings.flow, position.xz(), Some(entity))
+  }
+
+  /// Wave height relative to the water level at the wave coordinate `p` (with flow and amplitude).
+  ///
+  /// Shared by the flat and triplanar (sphere/cube) queries.
+  fn wave_offset(&self, flow: &WaterFlow, p: Vec2) -> f32 {
     let time = self.time.elapsed_secs_wrapped();
-    let p = position.xz();
+    let wave_time = self.wave_state.wave_time(time);
+    let wave_length = self.settings.wave_length.max(0.001);
+
+    let wave = if flow.is_enabled() {
+      let velocity = flow.velocity_at(self.images.as_deref(), p);
+      sample_flow_advected(time, velocity, flow.period, p, |p| {
+        self.sample_wave(wave_time, p / wave_length)
+      })
+    } else {
+      self.sample_wave(wave_time, p / wave_length)
+    };
+
+    self.settings.amplitude * wave
+  }
+
+  /// Wave height relative to the water level at the flat wave coordinate `p`.
+  fn flat_wave_offset(&self, flow: &WaterFlow, p: Vec2, exclude: Option<Entity>) -> f32 {
+    let wake = self
+      .wakes
+      .as_ref()
+      .map(|wakes| match exclude {
+        Some(entity) => wakes.height_at_excluding(p, entity),
+        None => wakes.height_at(p),
+      })
+      .unwrap_or_default();
+    // Ripples and wakes are only on flat world-aligned water, like in the water shader.
+    let ripple = self
+      .ripples
+      .as_ref()
+      .map(|ripples| ripples.height_at(p))
+      .unwrap_or_default();
+
+    self.wave_offset(flow, p) + wake + ripple
+  }
+
+  /// Calculates the radius of a spherical water surface (see `WaterSphere`) at the given position.
+  ///
+  /// # Arguments
+  ///
+  /// * `center` - The global position of the planet center.
+  /// * `radius` - The radius of the calm water surface.
+  /// * `position` - The global position, only the direction from `center` is used.
+  ///
+  /// # Returns
+  ///
+  /// The distance from `center` to the water surface along the radial direction through `position`.
+  pub fn sphere_wave_height(&self, center: Vec3, radius: f32, position: Vec3) -> f32 {
+    let n = (position - center).normalize_or(Vec3::Y);
+    radius + self.triplanar_wave(n * radius, n)
+  }
 
+  /// Triplanar wave height at the 3d wave coordinate `q`, blended by the direction `n`.
+  ///
+  /// Matches the triplanar projection of the water shader.
+  fn triplanar_wave(&self, q: Vec3, n: Vec3) -> f32 {
+    let w = n.abs().powf(4.0);
+    let w = w / (w.x + w.y + w.z);
+    let flow = &self.settings.flow;
+    self.wave_offset(flow, q.zy()) * w.x
+      + self.wave_offset(flow, q.xz()) * w.y
+      + self.wave_offset(flow, q.xy()) * w.z
+  }
+
+  /// Calculates the point on a cube or cube-sphere water surface (see `CubeWaterMeshBuilder`).
+  ///
+  /// # Arguments
+  ///
+  /// * `center` - The global position of the cube center.
+  /// * `half_size` - The half size of the cube (or radius of the cube-sphere).
+  /// * `spherical` - The mesh is a cube-sphere.
+  /// * `position` - The global position, only the direction from `center` is used.
+  ///
+  /// # Returns
+  ///
+  /// The point on the water surface along the direction from `center` through `position`.
+  pub fn cube_wave_point(
+    &self,
+    center: Vec3,
+    half_size: f32,
+    spherical: bool,
+    position: Vec3,
+  ) -> Vec3 {
+    let dir = (position - center).normalize_or(Vec3::Y);
+    // Point on the cube surface, the wave coordinate of the cube water material.
+    let cube_coord = dir / dir.abs().max_element() * half_size;
+    let radial = cube_coord.normalize();
+    let calm = if spherical {
+      radial * half_size
+    } else {
+      cube_coord
+    };
+    center + calm + radial * self.triplanar_wave(cube_coord, radial)
+  }
+
+  /// Calculates the point on a spherical water surface above or below the given position.
+  pub fn sphere_wave_point(&self, center: Vec3, radius: f32, position: Vec3) -> Vec3 {
+    let n = (position - center).normalize_or(Vec3::Y);
+    center + n * self.sphere_wave_height(center, radius, position)
+  }
+
+  /// Sample the wave pattern without flow or amplitude.
+  fn sample_wave(&self, time: f32, p: Vec2) -> f32 {
     // Use dual-sample crossfade for High/Ultra quality (matches shader behavior)
-    let wave = match self.settings.water_quality {
+    match self.settings.water_quality {
       WaterQuality::High | WaterQuality::Ultra => sample_directional_wave_blended(
         time,
         p,
@@ -46,9 +184,27 @@ impl<'w> WaterParam<'w> {
           self.settings.water_quality.into(),
         )
       }
-    };
+    }
+  }
+
+  /// Calculates the flow velocity of the water at the given position.
+  ///
+  /// # Arguments
+  ///
+  /// * `position` - The global position at which to sample the flow.
+  ///
+  /// # Returns
+  ///
+  /// The flow velocity on the X/Z plane in world units per second.  Use it to drift floating objects with the current.
+  ///
+  /// Only `WaterSettings::flow` is used, `WaterBodies::flow_velocity` also handles water bodies with their own `WaterFlow`.
+  pub fn flow_velocity(&self, position: Vec3) -> Vec2 {
+    self.flow_velocity_with_flow(&self.settings.flow, position)
+  }
 
-    self.settings.height + self.settings.amplitude * wave
+  /// Calculates the flow velocity at the given global position for a water body with its own `WaterFlow`.
+  pub fn flow_velocity_with_flow(&self, flow: &WaterFlow, position: Vec3) -> Vec2 {
+    flow.velocity_at(self.images.as_deref(), position.xz())
   }
 
   /// Calculates the point of the waves at the given position.
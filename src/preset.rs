@@ -0,0 +1,587 @@
+use bevy::{
+  asset::{io::Reader, AssetLoader, LoadContext},
+  image::{
+    ImageAddressMode, ImageFilterMode, ImageLoaderSettings, ImageSampler, ImageSamplerDescriptor,
+  },
+  platform::collections::HashSet,
+  prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  material::StandardWaterMaterial, WaterFlow, WaterQuality, WaterSettings, WaterTileSubdivisions,
+  WaveDirection,
+};
+
+/// Water settings loaded from a RON file (`*.water.ron`).
+///
+/// Every field of the file is optional and defaults to `WaterSettings::default()`:
+///
+/// ```ron
+/// (
+///   amplitude: 2.5,
+///   clarity: 0.1,
+///   deep_color: Srgba((red: 0.05, green: 0.2, blue: 0.3, alpha: 1.0)),
+///   wave_direction: (1.0, 0.5),
+///   detail_normal_map: Some("textures/water_normals.png"),
+///   transition_duration: 5.0,
+/// )
+/// ```
+///
+/// Assign the preset to the global `WaterSettings` or to a water body with
+/// `WaterPresetHandle`.  With the `file_watcher` feature, edits to the file are
+/// hot-reloaded and the water transitions smoothly to the new values.
+#[derive(Asset, Clone, Debug, Reflect)]
+pub struct WaterPreset {
+  /// The water settings.
+  pub settings: WaterSettings,
+  /// Duration in seconds of the transition when the preset is assigned or reloaded.
+  pub transition_duration: f32,
+}
+
+impl WaterPreset {
+  /// Parse a preset from RON, textures are loaded with `load_context`.
+  fn from_file(file: WaterPresetFile, load_context: &mut LoadContext) -> Self {
+    let normal_map = |path: String, load_context: &mut LoadContext| {
+      load_context
+        .loader()
+        .with_settings(|settings: &mut ImageLoaderSettings| {
+          settings.is_srgb = false;
+          settings.sampler = ImageSampler::Descriptor(repeat_sampler());
+        })
+        .load(path)
+    };
+    let flow_map = |path: String, load_context: &mut LoadContext| {
+      load_context
+        .loader()
+        .with_settings(|settings: &mut ImageLoaderSettings| {
+          settings.is_srgb = false;
+        })
+        .load(path)
+    };
+    let settings = WaterSettings {
+      alpha_mode: file.alpha_mode.into(),
+      height: file.height,
+      amplitude: file.amplitude,
+      base_color: file.base_color,
+      clarity: file.clarity,
+      deep_color: file.deep_color,
+      shallow_color: file.shallow_color,
+      edge_scale: file.edge_scale,
+      edge_color: file.edge_color,
+      update_materials: file.update_materials,
+      spawn_tiles: file.spawn_tiles,
+      tile_size: file.tile_size,
+      tile_subdivisions: file.tile_subdivisions,
+      water_quality: file.water_quality,
+      wave_direction: file.wave_direction,
+      wave_direction_blend_duration: file.wave_direction_blend_duration,
+      refraction: file.refraction,
+      refraction_strength: file.refraction_strength,
+      refraction_dispersion: file.refraction_dispersion,
+      subsurface_color: file.subsurface_color,
+      subsurface_strength: file.subsurface_strength,
+      detail_normal_map: file
+        .detail_normal_map
+        .map(|path| normal_map(path, load_context)),
+      detail_normal_strength: file.detail_normal_strength,
+      detail_normal_scale: file.detail_normal_scale,
+      detail_normal_speed: file.detail_normal_speed,
+      detail_fade_distance: file.detail_fade_distance,
+      flow: WaterFlow {
+        velocity: file.flow.velocity,
+        flow_map: file.flow.flow_map.map(|path| flow_map(path, load_context)),
+        flow_map_scale: file.flow.flow_map_scale,
+        flow_map_rect: file.flow.flow_map_rect,
+        period: file.flow.period,
+      },
+      wave_length: file.wave_length,
+      wave_speed: file.wave_speed,
+      rain_intensity: file.rain_intensity,
+      rain_ripple_size: file.rain_ripple_size,
+      rain_ripple_strength: file.rain_ripple_strength,
+    };
+    Self {
+      settings,
+      transition_duration: file.transition_duration,
+    }
+  }
+}
+
+/// Sampler for tiling detail normal maps.
+fn repeat_sampler() -> ImageSamplerDescriptor {
+  ImageSamplerDescriptor {
+    address_mode_u: ImageAddressMode::Repeat,
+    address_mode_v: ImageAddressMode::Repeat,
+    address_mode_w: ImageAddressMode::Repeat,
+    mag_filter: ImageFilterMode::Linear,
+    min_filter: ImageFilterMode::Linear,
+    mipmap_filter: ImageFilterMode::Linear,
+    ..default()
+  }
+}
+
+/// The RON file format of a `WaterPreset`.
+#[derive(Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct WaterPresetFile {
+  alpha_mode: PresetAlphaMode,
+  height: f32,
+  amplitude: f32,
+  base_color: Color,
+  clarity: f32,
+  deep_color: Color,
+  shallow_color: Color,
+  edge_scale: f32,
+  edge_color: Color,
+  update_materials: bool,
+  spawn_tiles: Option<UVec2>,
+  tile_size: f32,
+  tile_subdivisions: WaterTileSubdivisions,
+  water_quality: WaterQuality,
+  wave_direction: Vec2,
+  wave_direction_blend_duration: f32,
+  refraction: bool,
+  refraction_strength: f32,
+  refraction_dispersion: f32,
+  subsurface_color: Color,
+  subsurface_strength: f32,
+  /// Asset path of the detail normal map.
+  detail_normal_map: Option<String>,
+  detail_normal_strength: f32,
+  detail_normal_scale: Vec2,
+  detail_normal_speed: f32,
+  detail_fade_distance: f32,
+  flow: PresetFlow,
+  wave_length: f32,
+  wave_speed: f32,
+  rain_intensity: f32,
+  rain_ripple_size: f32,
+  rain_ripple_strength: f32,
+  transition_duration: f32,
+}
+
+impl Default for WaterPresetFile {
+  fn default() -> Self {
+    let settings = WaterSettings::default();
+    Self {
+      alpha_mode: settings.alpha_mode.into(),
+      height: settings.height,
+      amplitude: settings.amplitude,
+      base_color: settings.base_color,
+      clarity: settings.clarity,
+      deep_color: settings.deep_color,
+      shallow_color: settings.shallow_color,
+      edge_scale: settings.edge_scale,
+      edge_color: settings.edge_color,
+      update_materials: settings.update_materials,
+      spawn_tiles: settings.spawn_tiles,
+      tile_size: settings.tile_size,
+      tile_subdivisions: settings.tile_subdivisions,
+      water_quality: settings.water_quality,
+      wave_direction: settings.wave_direction,
+      wave_direction_blend_duration: settings.wave_direction_blend_duration,
+      refraction: settings.refraction,
+      refraction_strength: settings.refraction_strength,
+      refraction_dispersion: settings.refraction_dispersion,
+      subsurface_color: settings.subsurface_color,
+      subsurface_strength: settings.subsurface_strength,
+      detail_normal_map: None,
+      detail_normal_strength: settings.detail_normal_strength,
+      detail_normal_scale: settings.detail_normal_scale,
+      detail_normal_speed: settings.detail_normal_speed,
+      detail_fade_distance: settings.detail_fade_distance,
+      flow: PresetFlow::default(),
+      wave_length: settings.wave_length,
+      wave_speed: settings.wave_speed,
+      rain_intensity: settings.rain_intensity,
+      rain_ripple_size: settings.rain_ripple_size,
+      rain_ripple_strength: settings.rain_ripple_strength,
+      transition_duration: 2.0,
+    }
+  }
+}
+
+/// The RON file format of a `WaterFlow`.
+#[derive(Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct PresetFlow {
+  velocity: Vec2,
+  /// Asset path of the flow map.
+  flow_map: Option<String>,
+  flow_map_scale: f32,
+  flow_map_rect: Rect,
+  period: f32,
+}
+
+impl Default for PresetFlow {
+  fn default() -> Self {
+    let flow = WaterFlow::default();
+    Self {
+      velocity: flow.velocity,
+      flow_map: None,
+      flow_map_scale: flow.flow_map_scale,
+      flow_map_rect: flow.flow_map_rect,
+      period: flow.period,
+    }
+  }
+}
+
+/// `AlphaMode` for RON files.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+enum PresetAlphaMode {
+  Opaque,
+  Mask(f32),
+  Blend,
+  Premultiplied,
+  AlphaToCoverage,
+  Add,
+  Multiply,
+}
+
+impl From<AlphaMode> for PresetAlphaMode {
+  fn from(mode: AlphaMode) -> Self {
+    match mode {
+      AlphaMode::Opaque => Self::Opaque,
+      AlphaMode::Mask(cutoff) => Self::Mask(cutoff),
+      AlphaMode::Blend => Self::Blend,
+      AlphaMode::Premultiplied => Self::Premultiplied,
+      AlphaMode::AlphaToCoverage => Self::AlphaToCoverage,
+      AlphaMode::Add => Self::Add,
+      AlphaMode::Multiply => Self::Multiply,
+    }
+  }
+}
+
+impl From<PresetAlphaMode> for AlphaMode {
+  fn from(mode: PresetAlphaMode) -> Self {
+    match mode {
+      PresetAlphaMode::Opaque => Self::Opaque,
+      PresetAlphaMode::Mask(cutoff) => Self::Mask(cutoff),
+      PresetAlphaMode::Blend => Self::Blend,
+      PresetAlphaMode::Premultiplied => Self::Premultiplied,
+      PresetAlphaMode::AlphaToCoverage => Self::AlphaToCoverage,
+      PresetAlphaMode::Add => Self::Add,
+      PresetAlphaMode::Multiply => Self::Multiply,
+    }
+  }
+}
+
+/// Error loading a `WaterPreset`.
+#[derive(Debug)]
+pub enum WaterPresetLoaderError {
+  /// Failed to read the file.
+  Io(std::io::Error),
+  /// Failed to parse the RON.
+  Ron(ron::error::SpannedError),
+}
+
+impl std::fmt::Display for WaterPresetLoaderError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Io(err) => write!(f, "Could not read water preset: {err}"),
+      Self::Ron(err) => write!(f, "Could not parse water preset: {err}"),
+    }
+  }
+}
+
+impl std::error::Error for WaterPresetLoaderError {}
+
+impl From<std::io::Error> for WaterPresetLoaderError {
+  fn from(err: std::io::Error) -> Self {
+    Self::Io(err)
+  }
+}
+
+impl From<ron::error::SpannedError> for WaterPresetLoaderError {
+  fn from(err: ron::error::SpannedError) -> Self {
+    Self::Ron(err)
+  }
+}
+
+/// Loads `WaterPreset` assets from `*.water.ron` files.
+#[derive(Default, TypePath)]
+pub struct WaterPresetLoader;
+
+impl AssetLoader for WaterPresetLoader {
+  type Asset = WaterPreset;
+  type Settings = ();
+  type Error = WaterPresetLoaderError;
+
+  async fn load(
+    &self,
+    reader: &mut dyn Reader,
+    _settings: &(),
+    load_context: &mut LoadContext<'_>,
+  ) -> Result<Self::Asset, Self::Error> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes).await?;
+    let file: WaterPresetFile = ron::de::from_bytes(&bytes)?;
+    Ok(WaterPreset::from_file(file, load_context))
+  }
+
+  fn extensions(&self) -> &[&str] {
+    &["water.ron"]
+  }
+}
+
+impl WaterSettings {
+  /// Interpolate from these settings to `other`.
+  ///
+  /// Numbers are interpolated linearly and colors in the Oklab color space.  Everything
+  /// else (the tile grid, quality, refraction, textures, wave direction) keeps the values of
+  /// `self` until `t` reaches 1.0, so a transition doesn't rebuild the water tiles or
+  /// recompile the shaders on its first frame.  The wave direction has its own crossfade
+  /// (see `WaveDirection`).
+  pub fn lerp(&self, other: &Self, t: f32) -> Self {
+    self.lerp_switching(other, t, t >= 1.0)
+  }
+
+  /// `lerp`, the discrete fields switch to `other` if `switch` is true.
+  ///
+  /// Eased blends overshoot `t = 1.0` before the end, the switch follows the blend's progress.
+  pub(crate) fn lerp_switching(&self, other: &Self, t: f32, switch: bool) -> Self {
+    let t = t.clamp(0.0, 1.0);
+    let f = |a: f32, b: f32| a + (b - a) * t;
+    // Perceptual color space, avoids muddy colors halfway.
+    let c = |a: Color, b: Color| Color::from(Oklaba::from(a).mix(&Oklaba::from(b), t));
+    let discrete = if switch { other } else { self };
+    let mut flow = discrete.flow.clone();
+    flow.velocity = self.flow.velocity.lerp(other.flow.velocity, t);
+    flow.flow_map_scale = f(self.flow.flow_map_scale, other.flow.flow_map_scale);
+    flow.period = f(self.flow.period, other.flow.period);
+    Self {
+      height: f(self.height, other.height),
+      amplitude: f(self.amplitude, other.amplitude),
+      base_color: c(self.base_color, other.base_color),
+      clarity: f(self.clarity, other.clarity),
+      deep_color: c(self.deep_color, other.deep_color),
+      shallow_color: c(self.shallow_color, other.shallow_color),
+      edge_scale: f(self.edge_scale, other.edge_scale),
+      edge_color: c(self.edge_color, other.edge_color),
+      refraction_strength: f(self.refraction_strength, other.refraction_strength),
+      refraction_dispersion: f(self.refraction_dispersion, other.refraction_dispersion),
+      subsurface_color: c(self.subsurface_color, other.subsurface_color),
+      subsurface_strength: f(self.subsurface_strength, other.subsurface_strength),
+      detail_normal_strength: f(self.detail_normal_strength, other.detail_normal_strength),
+      detail_normal_scale: self.detail_normal_scale.lerp(other.detail_normal_scale, t),
+      detail_normal_speed: f(self.detail_normal_speed, other.detail_normal_speed),
+      detail_fade_distance: f(self.detail_fade_distance, other.detail_fade_distance),
+      flow,
+      wave_length: f(self.wave_length, other.wave_length),
+      wave_speed: f(self.wave_speed, other.wave_speed),
+      rain_intensity: f(self.rain_intensity, other.rain_intensity),
+      rain_ripple_size: f(self.rain_ripple_size, other.rain_ripple_size),
+      rain_ripple_strength: f(self.rain_ripple_strength, other.rain_ripple_strength),
+      ..discrete.clone()
+    }
+  }
+}
+
+/// Apply a `WaterPreset`.
+///
+/// As a resource the preset drives the global `WaterSettings`.  As a component the
+/// preset drives the material of a water body (lakes, rivers, volumes), which is
+/// then no longer updated from the global `WaterSettings`.  The body needs its own
+/// material, the water tiles share a single material.
+///
+/// The `height` and `spawn_tiles` of the preset are ignored for water bodies,
+/// move the body's `Transform` instead.
+#[derive(Resource, Component, Clone, Debug, Default, Reflect)]
+#[reflect(Resource, Component, Default)]
+pub struct WaterPresetHandle {
+  /// The preset.
+  pub preset: Handle<WaterPreset>,
+  /// Settings applied last.
+  applied: Option<WaterSettings>,
+  /// Settings at the start of the transition and the elapsed time.
+  transition: Option<(WaterSettings, f32)>,
+}
+
+impl WaterPresetHandle {
+  pub fn new(preset: Handle<WaterPreset>) -> Self {
+    Self {
+      preset,
+      applied: None,
+      transition: None,
+    }
+  }
+
+  /// Settings to apply this frame.
+  ///
+  /// `reload` starts a transition from `current` (or the last applied settings)
+  /// to the preset.  The first time the preset is applied right away.
+  fn update(
+    &mut self,
+    presets: &Assets<WaterPreset>,
+    reload: bool,
+    current: Option<&WaterSettings>,
+    dt: f32,
+  ) -> Option<WaterSettings> {
+    let preset = presets.get(&self.preset)?;
+    if reload {
+      match current.or(self.applied.as_ref()) {
+        Some(from) if self.applied.is_some() => {
+          self.transition = Some((from.clone(), 0.0));
+        }
+        _ => {
+          self.transition = None;
+          self.applied = Some(preset.settings.clone());
+          return self.applied.clone();
+        }
+      }
+    }
+    let (from, elapsed) = self.transition.as_mut()?;
+    *elapsed += dt;
+    let t = if preset.transition_duration > 0.0 {
+      *elapsed / preset.transition_duration
+    } else {
+      1.0
+    };
+    let settings = from.lerp(&preset.settings, t);
+    if t >= 1.0 {
+      self.transition = None;
+    }
+    self.applied = Some(settings.clone());
+    self.applied.clone()
+  }
+}
+
+type PresetBodyQuery = (
+  &'static mut WaterPresetHandle,
+  &'static MeshMaterial3d<StandardWaterMaterial>,
+  Option<&'static WaterFlow>,
+  Option<&'static mut WaveDirection>,
+);
+
+/// Apply `WaterPresetHandle`s when assigned, loaded or hot-reloaded.
+pub fn apply_water_presets(
+  time: Res<Time>,
+  presets: Res<Assets<WaterPreset>>,
+  mut events: MessageReader<AssetEvent<WaterPreset>>,
+  global: Option<ResMut<WaterPresetHandle>>,
+  mut settings: ResMut<WaterSettings>,
+  mut materials: ResMut<Assets<StandardWaterMaterial>>,
+  mut bodies: Query<PresetBodyQuery>,
+) {
+  let reloaded: HashSet<_> = events
+    .read()
+    .filter_map(|event| match event {
+      AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => Some(*id),
+      _ => None,
+    })
+    .collect();
+  let dt = time.delta_secs();
+
+  if let Some(mut global) = global {
+    let reload = global.is_changed() || reloaded.contains(&global.preset.id());
+    let global = global.bypass_change_detection();
+    if let Some(new_settings) = global.update(&presets, reload, Some(&settings), dt) {
+      *settings = new_settings;
+    }
+  }
+
+  for (mut preset, material, flow, mut wave_dir) in bodies.iter_mut() {
+    let reload = preset.is_changed() || reloaded.contains(&preset.preset.id());
+    let Some(body_settings) = preset
+      .bypass_change_detection()
+      .update(&presets, reload, None, dt)
+    else {
+      continue;
+    };
+    apply_body_settings(
+      &body_settings,
+      &mut materials,
+      material,
+      flow,
+      wave_dir.as_deref_mut(),
+    );
+  }
+}
+
+/// Apply settings to the material and wave direction of a water body.
+pub(crate) fn apply_body_settings(
+  settings: &WaterSettings,
+  materials: &mut Assets<StandardWaterMaterial>,
+  material: &MeshMaterial3d<StandardWaterMaterial>,
+  flow: Option<&WaterFlow>,
+  wave_dir: Option<&mut WaveDirection>,
+) {
+  if let Some(mat) = materials.get_mut(&material.0) {
+    settings.apply_base(&mut mat.base);
+    settings.apply_extension(&mut mat.extension);
+    // A `WaterFlow` component overrides the flow of the settings.
+    if let Some(flow) = flow {
+      flow.apply_to(&mut mat.extension);
+    }
+  }
+  if let Some(wave_dir) = wave_dir {
+    wave_dir.set_target(settings.wave_direction);
+    wave_dir.set_duration(settings.wave_direction_blend_duration);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::WaterQuality;
+
+  fn assert_color_eq(a: Color, b: Color) {
+    let (a, b) = (a.to_srgba().to_vec4(), b.to_srgba().to_vec4());
+    assert!(a.abs_diff_eq(b, 1e-4), "{a} != {b}");
+  }
+
+  #[test]
+  fn lerp_settings() {
+    let from = WaterSettings {
+      amplitude: 1.0,
+      height: 0.0,
+      deep_color: Color::srgb(1.0, 0.0, 0.0),
+      water_quality: WaterQuality::Basic,
+      spawn_tiles: Some(UVec2::new(4, 4)),
+      tile_size: 256.0,
+      refraction: false,
+      ..default()
+    };
+    let to = WaterSettings {
+      amplitude: 3.0,
+      height: 2.0,
+      deep_color: Color::srgb(0.0, 0.0, 1.0),
+      water_quality: WaterQuality::Ultra,
+      spawn_tiles: Some(UVec2::new(8, 8)),
+      tile_size: 128.0,
+      refraction: true,
+      ..default()
+    };
+
+    let start = from.lerp(&to, 0.0);
+    assert_eq!(start.amplitude, 1.0);
+    assert_color_eq(start.deep_color, from.deep_color);
+
+    let half = from.lerp(&to, 0.5);
+    assert_eq!(half.amplitude, 2.0);
+    assert_eq!(half.height, 1.0);
+    let oklab = Oklaba::from(from.deep_color).mix(&Oklaba::from(to.deep_color), 0.5);
+    assert_color_eq(half.deep_color, Color::from(oklab));
+    assert_ne!(
+      half.deep_color.to_srgba(),
+      from
+        .deep_color
+        .to_srgba()
+        .mix(&to.deep_color.to_srgba(), 0.5)
+    );
+    // The grid, quality and refraction don't switch early.
+    assert_eq!(half.water_quality, WaterQuality::Basic);
+    assert_eq!(half.spawn_tiles, from.spawn_tiles);
+    assert_eq!(half.tile_size, 256.0);
+    assert!(!half.refraction);
+
+    let end = from.lerp(&to, 1.0);
+    assert_eq!(end.amplitude, 3.0);
+    assert_color_eq(end.deep_color, to.deep_color);
+    assert_eq!(end.water_quality, WaterQuality::Ultra);
+    assert_eq!(end.spawn_tiles, to.spawn_tiles);
+    assert_eq!(end.tile_size, 128.0);
+    assert!(end.refraction);
+  }
+}
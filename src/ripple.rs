@@ -0,0 +1,427 @@
+use bevy::{
+  asset::RenderAssetUsages,
+  prelude::*,
+  render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+
+use crate::{material::StandardWaterMaterial, WaterParam};
+
+/// Local interactive ripple simulation.
+///
+/// A heightfield (wave equation on a grid) centered on the `RippleFocus` entity.
+/// Entities with `RippleEmitter` disturb the water when moving through the surface.
+/// The ripple height is added on top of the waves in the water shader and `WaterParam`.
+///
+/// Insert this resource to enable ripples.  The simulation only runs on the CPU,
+/// the heightfield is uploaded each frame to the water materials with world
+/// coordinates (`WaterMaterial::world_coords`, like the water tiles and lakes).
+#[derive(Resource, Clone, Debug, Reflect)]
+#[reflect(Resource, Default)]
+pub struct WaterRipples {
+  resolution: u32,
+  /// Size of a cell in world units.
+  pub cell_size: f32,
+  /// Speed of the ripples in world units per second.
+  pub wave_speed: f32,
+  /// Fraction of the ripple height kept per second.
+  pub damping: f32,
+  /// Fixed simulation time step in seconds.
+  pub time_step: f32,
+  /// Grid cell of the grid's corner.
+  origin: IVec2,
+  current: Vec<f32>,
+  previous: Vec<f32>,
+  accumulator: f32,
+  #[reflect(ignore)]
+  image: Option<Handle<Image>>,
+}
+
+impl Default for WaterRipples {
+  fn default() -> Self {
+    Self::new(128, 0.25)
+  }
+}
+
+impl WaterRipples {
+  /// Create a ripple grid with `resolution` x `resolution` cells of `cell_size`.
+  pub fn new(resolution: u32, cell_size: f32) -> Self {
+    let resolution = resolution.max(2);
+    let cells = (resolution * resolution) as usize;
+    Self {
+      resolution,
+      cell_size,
+      wave_speed: 2.0,
+      damping: 0.3,
+      time_step: 1.0 / 60.0,
+      origin: IVec2::splat(-(resolution as i32) / 2),
+      current: vec![0.0; cells],
+      previous: vec![0.0; cells],
+      accumulator: 0.0,
+      image: None,
+    }
+  }
+
+  /// Number of cells along each side of the grid.
+  pub fn resolution(&self) -> u32 {
+    self.resolution
+  }
+
+  /// World X/Z position of the grid's corner.
+  pub fn offset(&self) -> Vec2 {
+    self.origin.as_vec2() * self.cell_size
+  }
+
+  /// World size of the grid.
+  pub fn size(&self) -> Vec2 {
+    Vec2::splat(self.resolution as f32 * self.cell_size)
+  }
+
+  /// The heightfield image used by the water materials.
+  pub fn image(&self) -> Option<&Handle<Image>> {
+    self.image.as_ref()
+  }
+
+  /// Center the grid on the world X/Z position (snapped to whole cells).
+  ///
+  /// The ripples stay in place in the world, cells that scroll in start calm.
+  pub fn set_center(&mut self, center: Vec2) {
+    let half = self.resolution as i32 / 2;
+    let origin = (center / self.cell_size).floor().as_ivec2() - IVec2::splat(half);
+    let shift = origin - self.origin;
+    if shift == IVec2::ZERO {
+      return;
+    }
+    self.origin = origin;
+    let n = self.resolution as i32;
+    let scroll = |field: &Vec<f32>| -> Vec<f32> {
+      let mut out = vec![0.0; field.len()];
+      for y in 0..n {
+        for x in 0..n {
+          let (sx, sy) = (x + shift.x, y + shift.y);
+          if sx >= 0 && sy >= 0 && sx < n && sy < n {
+            out[(y * n + x) as usize] = field[(sy * n + sx) as usize];
+          }
+        }
+      }
+      out
+    };
+    self.current = scroll(&self.current);
+    self.previous = scroll(&self.previous);
+  }
+
+  /// Push the water surface down around the world X/Z `position`.
+  ///
+  /// `strength` is the depth in world units at the center, negative values raise the water.
+  pub fn disturb(&mut self, position: Vec2, radius: f32, strength: f32) {
+    let n = self.resolution as i32;
+    let radius = radius.max(self.cell_size);
+    let cell = position / self.cell_size - self.origin.as_vec2();
+    let reach = (radius / self.cell_size).ceil() as i32;
+    let (cx, cy) = (cell.x.floor() as i32, cell.y.floor() as i32);
+    for y in (cy - reach).max(0)..=(cy + reach).min(n - 1) {
+      for x in (cx - reach).max(0)..=(cx + reach).min(n - 1) {
+        let d = (Vec2::new(x as f32, y as f32) + 0.5 - cell).length() * self.cell_size;
+        if d < radius {
+          let falloff = 1.0 - (d / radius).powi(2);
+          let idx = (y * n + x) as usize;
+          // Displace both states, so the disturbance starts at rest.
+          self.current[idx] -= strength * falloff * falloff;
+          self.previous[idx] -= strength * falloff * falloff;
+        }
+      }
+    }
+  }
+
+  /// Advance the simulation by `dt` seconds (in fixed time steps).
+  pub fn step(&mut self, dt: f32) {
+    let time_step = self.time_step.max(0.001);
+    self.accumulator = (self.accumulator + dt).min(time_step * 8.0);
+    while self.accumulator >= time_step {
+      self.accumulator -= time_step;
+      self.step_fixed(time_step);
+    }
+  }
+
+  fn step_fixed(&mut self, dt: f32) {
+    let n = self.resolution as usize;
+    // Keep the explicit integration stable.
+    let c = (self.wave_speed * dt / self.cell_size).min(0.5);
+    let c2 = c * c;
+    // Scaling the new height by `d` damps the oscillation by `sqrt(d)` per step.
+    let damping = self.damping.clamp(0.0, 1.0).powf(2.0 * dt);
+    for y in 0..n {
+      for x in 0..n {
+        let idx = y * n + x;
+        // Edges are open (the heightfield is calm outside the grid).
+        let at = |x: usize, y: usize| self.current[y * n + x];
+        let left = if x > 0 { at(x - 1, y) } else { 0.0 };
+        let right = if x + 1 < n { at(x + 1, y) } else { 0.0 };
+        let down = if y > 0 { at(x, y - 1) } else { 0.0 };
+        let up = if y + 1 < n { at(x, y + 1) } else { 0.0 };
+        let h = self.current[idx];
+        let laplacian = left + right + down + up - 4.0 * h;
+        self.previous[idx] = (2.0 * h - self.previous[idx] + c2 * laplacian) * damping;
+      }
+    }
+    std::mem::swap(&mut self.current, &mut self.previous);
+  }
+
+  /// Ripple height at the world X/Z position (bilinear, matches the water shader).
+  pub fn height_at(&self, position: Vec2) -> f32 {
+    let n = self.resolution as i32;
+    let texel = position / self.cell_size - self.origin.as_vec2() - 0.5;
+    let base = texel.floor();
+    let f = texel - base;
+    let fetch = |x: i32, y: i32| -> f32 {
+      if x < 0 || y < 0 || x >= n || y >= n {
+        0.0
+      } else {
+        self.current[(y * n + x) as usize]
+      }
+    };
+    let (x, y) = (base.x as i32, base.y as i32);
+    let a = fetch(x, y).lerp(fetch(x + 1, y), f.x);
+    let b = fetch(x, y + 1).lerp(fetch(x + 1, y + 1), f.x);
+    a.lerp(b, f.y)
+  }
+
+  fn image_data(&self) -> Vec<u8> {
+    self.current.iter().flat_map(|h| h.to_le_bytes()).collect()
+  }
+}
+
+/// The ripple grid follows this entity (usually the camera or the player).
+#[derive(Component, Clone, Copy, Debug, Default, Reflect)]
+#[reflect(Component, Default)]
+pub struct RippleFocus;
+
+/// Disturbs the water when the entity moves through the surface.
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[reflect(Component, Default)]
+pub struct RippleEmitter {
+  /// Radius of the disturbance.
+  pub radius: f32,
+  /// Depth of the disturbance per unit of speed.
+  pub strength: f32,
+  last_position: Option<Vec3>,
+  /// Disturbance depth waiting for the next simulation step.
+  pending: f32,
+}
+
+impl Default for RippleEmitter {
+  fn default() -> Self {
+    Self::new(0.5, 0.02)
+  }
+}
+
+impl RippleEmitter {
+  /// Create an emitter with the disturbance `radius` and `strength` per unit of speed.
+  pub fn new(radius: f32, strength: f32) -> Self {
+    Self {
+      radius,
+      strength,
+      last_position: None,
+      pending: 0.0,
+    }
+  }
+}
+
+/// Measure how much `RippleEmitter` entities disturb the water surface.
+pub fn emit_water_ripples(
+  water: WaterParam,
+  mut emitters: Query<(&mut RippleEmitter, &GlobalTransform)>,
+) {
+  for (mut emitter, transform) in emitters.iter_mut() {
+    let position = transform.translation();
+    let Some(last) = emitter.last_position.replace(position) else {
+      continue;
+    };
+    // Only disturb the water while crossing the surface.
+    let surface = water.wave_height(position);
+    if (position.y - surface).abs() > emitter.radius {
+      continue;
+    }
+    emitter.pending += emitter.strength * (position - last).length();
+  }
+}
+
+/// Step the ripple simulation and upload the heightfield to the water materials.
+pub fn update_water_ripples(
+  time: Res<Time>,
+  mut ripples: ResMut<WaterRipples>,
+  mut images: ResMut<Assets<Image>>,
+  mut materials: ResMut<Assets<StandardWaterMaterial>>,
+  focus: Query<&GlobalTransform, With<RippleFocus>>,
+  mut emitters: Query<(&mut RippleEmitter, &GlobalTransform)>,
+) {
+  if let Some(focus) = focus.iter().next() {
+    ripples.set_center(focus.translation().xz());
+  }
+
+  for (mut emitter, transform) in emitters.iter_mut() {
+    if emitter.pending != 0.0 {
+      ripples.disturb(
+        transform.translation().xz(),
+        emitter.radius,
+        emitter.pending,
+      );
+      emitter.pending = 0.0;
+    }
+  }
+
+  ripples.step(time.delta_secs());
+
+  // Upload the heightfield.
+  let data = ripples.image_data();
+  match ripples
+    .image
+    .as_ref()
+    .and_then(|handle| images.get_mut(handle))
+  {
+    Some(image) => {
+      image.data = Some(data);
+    }
+    None => {
+      let size = Extent3d {
+        width: ripples.resolution,
+        height: ripples.resolution,
+        depth_or_array_layers: 1,
+      };
+      let image = Image::new(
+        size,
+        TextureDimension::D2,
+        data,
+        TextureFormat::R32Float,
+        RenderAssetUsages::default(),
+      );
+      ripples.image = Some(images.add(image));
+    }
+  }
+
+  // Only touch the materials when the grid moved (or for new materials).
+  let offset = ripples.offset();
+  let size = ripples.size();
+  let stale: Vec<_> = materials
+    .iter()
+    .filter(|(_, mat)| mat.extension.is_world_aligned())
+    .filter(|(_, mat)| {
+      mat.extension.ripple_map != ripples.image
+        || mat.extension.ripple_offset != offset
+        || mat.extension.ripple_size != size
+    })
+    .map(|(id, _)| id)
+    .collect();
+  for id in stale {
+    if let Some(mat) = materials.get_mut(id) {
+      mat.extension.ripple_map = ripples.image.clone();
+      mat.extension.ripple_offset = offset;
+      mat.extension.ripple_size = size;
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Sum of the squared heights.
+  fn energy(ripples: &WaterRipples) -> f32 {
+    ripples.current.iter().map(|h| h * h).sum()
+  }
+
+  #[test]
+  fn disturbance_propagates_outwards() {
+    let mut ripples = WaterRipples::new(64, 0.25);
+    ripples.damping = 1.0;
+    ripples.disturb(Vec2::ZERO, 0.5, 0.1);
+    assert!(ripples.height_at(Vec2::ZERO) < -0.05);
+    assert_eq!(ripples.height_at(Vec2::new(2.0, 0.0)), 0.0);
+
+    // Half a second at 2 units per second.
+    for _ in 0..30 {
+      ripples.step(1.0 / 60.0);
+    }
+    let ring = (0..8)
+      .map(|i| ripples.height_at(Vec2::from_angle(i as f32) * 1.25).abs())
+      .fold(0.0, f32::max);
+    assert!(ring > 0.001, "the ripple didn't reach the ring: {ring}");
+    // Not faster than the wave speed.
+    let far = ripples.height_at(Vec2::new(6.0, 0.0)).abs();
+    assert!(far < ring * 0.01, "{far}");
+    // Symmetric.
+    let (a, b) = (
+      ripples.height_at(Vec2::new(1.0, 0.0)),
+      ripples.height_at(Vec2::new(-1.0, 0.0)),
+    );
+    assert!((a - b).abs() < 1e-4, "{a} != {b}");
+  }
+
+  #[test]
+  fn ripples_are_damped() {
+    let mut damped = WaterRipples::new(64, 0.25);
+    let mut undamped = damped.clone();
+    undamped.damping = 1.0;
+    for ripples in [&mut damped, &mut undamped] {
+      ripples.disturb(Vec2::ZERO, 0.5, 0.1);
+    }
+    let start = energy(&damped);
+    for _ in 0..60 {
+      damped.step(1.0 / 60.0);
+      undamped.step(1.0 / 60.0);
+    }
+    // `damping` is the fraction of the ripple height kept per second.
+    let kept = (energy(&damped) / energy(&undamped)).sqrt();
+    assert!((kept - damped.damping).abs() < 0.05, "kept {kept}");
+    for _ in 0..600 {
+      damped.step(1.0 / 60.0);
+    }
+    assert!(energy(&damped) < start * 1e-4);
+    assert!(damped.current.iter().all(|h| h.is_finite()));
+  }
+
+  #[test]
+  fn ripples_stay_in_place_when_the_grid_moves() {
+    let mut ripples = WaterRipples::new(64, 0.25);
+    let p = Vec2::new(1.0, -2.0);
+    ripples.disturb(p, 0.5, 0.1);
+    ripples.step(0.1);
+    let height = ripples.height_at(p);
+    ripples.set_center(Vec2::new(3.0, 1.0));
+    assert_eq!(ripples.height_at(p), height);
+    // Scrolled out of the grid.
+    ripples.set_center(Vec2::new(100.0, 0.0));
+    assert_eq!(ripples.height_at(p), 0.0);
+    assert!(ripples.current.iter().all(|h| *h == 0.0));
+  }
+
+  #[test]
+  fn ripples_are_only_on_flat_water() {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use crate::{GlobalWaveState, WaterParam, WaterSettings};
+
+    let mut world = World::new();
+    world.init_resource::<WaterSettings>();
+    world.init_resource::<GlobalWaveState>();
+    world.init_resource::<Time>();
+    let heights = |world: &mut World| {
+      world
+        .run_system_once(|water: WaterParam| {
+          (
+            water.wave_height(Vec3::ZERO),
+            water.sphere_wave_height(Vec3::NEG_Y * 100.0, 100.0, Vec3::ZERO),
+          )
+        })
+        .unwrap()
+    };
+    let (flat, sphere) = heights(&mut world);
+
+    let mut ripples = WaterRipples::new(64, 0.25);
+    ripples.disturb(Vec2::ZERO, 0.5, 0.1);
+    let ripple = ripples.height_at(Vec2::ZERO);
+    world.insert_resource(ripples);
+    let (rippled_flat, rippled_sphere) = heights(&mut world);
+    assert!((rippled_flat - flat - ripple).abs() < 1e-5);
+    assert_eq!(rippled_sphere, sphere);
+  }
+}
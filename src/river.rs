@@ -0,0 +1,399 @@
+use bevy::{
+  asset::RenderAssetUsages,
+  light::NotShadowCaster,
+  math::Curve,
+  mesh::{Indices, PrimitiveTopology},
+  prelude::*,
+  render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+
+use crate::{material::StandardWaterMaterial, WaterFlow, WaterSettings};
+
+/// A sample along the center line of a river (in the river entity's local space).
+#[derive(Clone, Copy, Debug, Default, Reflect)]
+pub struct RiverPoint {
+  /// Center of the water surface.
+  pub position: Vec3,
+  /// Direction across the river (perpendicular to the flow, on the X/Z plane).
+  pub right: Vec3,
+  /// Width of the river.
+  pub width: f32,
+  /// Distance along the river from the source.
+  pub distance: f32,
+  /// Flow speed in world units per second.
+  pub speed: f32,
+}
+
+impl RiverPoint {
+  fn lerp(&self, other: &Self, t: f32) -> Self {
+    Self {
+      position: self.position.lerp(other.position, t),
+      right: self.right.lerp(other.right, t).normalize_or_zero(),
+      width: self.width.lerp(other.width, t),
+      distance: self.distance.lerp(other.distance, t),
+      speed: self.speed.lerp(other.speed, t),
+    }
+  }
+}
+
+/// The projection of a point onto a river.
+#[derive(Clone, Copy, Debug)]
+pub struct RiverProjection {
+  /// The interpolated river sample closest to the point.
+  pub point: RiverPoint,
+  /// Signed distance from the center line along `point.right`.
+  pub lateral: f32,
+  /// The point is past the start or end of the river.
+  pub outside_ends: bool,
+}
+
+impl RiverProjection {
+  /// Returns true if the projected point is over the river's water surface.
+  pub fn is_inside(&self) -> bool {
+    !self.outside_ends && self.lateral.abs() <= self.point.width / 2.0
+  }
+
+  /// Wave coordinates (matches the river mesh UVs).
+  pub fn coord(&self) -> Vec2 {
+    Vec2::new(self.lateral, self.point.distance)
+  }
+
+  /// Flow direction (local space).
+  pub fn direction(&self) -> Vec3 {
+    self.point.right.cross(Vec3::NEG_Y).normalize_or_zero()
+  }
+}
+
+/// A river water body.  Spawned by `RiverBuilder::spawn`.
+///
+/// The river mesh UVs are world-unit wave coordinates: `x` across the river and `y` along the flow.
+#[derive(Component, Clone, Debug, Default, Reflect)]
+#[reflect(Component, Default)]
+pub struct River {
+  points: Vec<RiverPoint>,
+}
+
+impl River {
+  /// Samples along the river's center line.
+  pub fn points(&self) -> &[RiverPoint] {
+    &self.points
+  }
+
+  /// Length of the river.
+  pub fn length(&self) -> f32 {
+    self.points.last().map(|p| p.distance).unwrap_or_default()
+  }
+
+  /// Interpolated center line point at `distance` from the source (clamped to the river).
+  pub fn point_at(&self, distance: f32) -> Option<RiverPoint> {
+    let next = self
+      .points
+      .partition_point(|p| p.distance < distance)
+      .clamp(1, self.points.len().max(1));
+    match self.points.get(next - 1..=next) {
+      Some([a, b]) if b.distance > a.distance => {
+        let t = ((distance - a.distance) / (b.distance - a.distance)).clamp(0.0, 1.0);
+        Some(a.lerp(b, t))
+      }
+      Some([a, _]) => Some(*a),
+      _ => self.points.first().copied(),
+    }
+  }
+
+  /// Project the local position `p` onto the river's center line (on the X/Z plane).
+  pub fn project(&self, p: Vec3) -> Option<RiverProjection> {
+    let mut best: Option<(f32, RiverProjection)> = None;
+    for (idx, segment) in self.points.windows(2).enumerate() {
+      let (a, b) = (&segment[0], &segment[1]);
+      let ab = (b.position - a.position).xz();
+      let len_sq = ab.length_squared();
+      let raw_t = if len_sq > 0.0 {
+        (p.xz() - a.position.xz()).dot(ab) / len_sq
+      } else {
+        0.0
+      };
+      let t = raw_t.clamp(0.0, 1.0);
+      let point = a.lerp(b, t);
+      let offset = p - point.position;
+      let dist_sq = offset.xz().length_squared();
+      if best.as_ref().is_none_or(|(d, _)| dist_sq < *d) {
+        let first = idx == 0 && raw_t < 0.0;
+        let last = idx + 2 == self.points.len() && raw_t > 1.0;
+        best = Some((
+          dist_sq,
+          RiverProjection {
+            point,
+            lateral: offset.dot(point.right),
+            outside_ends: first || last,
+          },
+        ));
+      }
+    }
+    best.map(|(_, projection)| projection)
+  }
+
+  /// Returns true if the local position `p` is over the river's water surface.
+  pub fn contains(&self, p: Vec3) -> bool {
+    self
+      .project(p)
+      .is_some_and(|projection| projection.is_inside())
+  }
+}
+
+/// Builder for river water bodies following a curve.
+///
+/// ```ignore
+/// let curve = CubicCardinalSpline::new_catmull_rom(points).to_curve()?;
+/// RiverBuilder::from_curve(&curve, 64)
+///   .width(12.0)
+///   .spawn(&mut commands, &mut meshes, &mut images, &mut materials, &settings);
+/// ```
+#[derive(Clone, Debug)]
+pub struct RiverBuilder {
+  /// Center line samples of the water surface.
+  pub center: Vec<Vec3>,
+  /// Width of the river at each center line sample.
+  pub widths: Vec<f32>,
+  /// Number of quads across the river.
+  pub cross_segments: u32,
+  /// Flow speed on flat ground.
+  pub base_speed: f32,
+  /// Extra flow speed per unit of drop per unit of distance.
+  pub slope_speed: f32,
+}
+
+impl RiverBuilder {
+  /// Sample the water surface center line from a curve.
+  pub fn from_curve(curve: &impl Curve<Vec3>, segments: usize) -> Self {
+    let domain = curve.domain();
+    let segments = segments.max(1);
+    let center = (0..=segments)
+      .map(|i| {
+        let t = domain.start() + domain.length() * i as f32 / segments as f32;
+        curve.sample_clamped(t)
+      })
+      .collect();
+    Self::from_points(center)
+  }
+
+  /// Create a river from center line points.
+  pub fn from_points(center: Vec<Vec3>) -> Self {
+    let widths = vec![10.0; center.len()];
+    Self {
+      center,
+      widths,
+      cross_segments: 4,
+      base_speed: 1.0,
+      slope_speed: 20.0,
+    }
+  }
+
+  /// Set a constant width.
+  pub fn width(mut self, width: f32) -> Self {
+    self.widths.fill(width);
+    self
+  }
+
+  /// Set the width from a profile, `t` goes from 0.0 (source) to 1.0 (mouth).
+  pub fn width_profile(mut self, profile: impl Fn(f32) -> f32) -> Self {
+    let last = (self.center.len().max(2) - 1) as f32;
+    for (i, width) in self.widths.iter_mut().enumerate() {
+      *width = profile(i as f32 / last);
+    }
+    self
+  }
+
+  /// Offset the water height with a profile, `t` goes from 0.0 (source) to 1.0 (mouth).
+  pub fn height_profile(mut self, profile: impl Fn(f32) -> f32) -> Self {
+    let last = (self.center.len().max(2) - 1) as f32;
+    for (i, center) in self.center.iter_mut().enumerate() {
+      center.y += profile(i as f32 / last);
+    }
+    self
+  }
+
+  /// Set the number of quads across the river.
+  pub fn cross_segments(mut self, cross_segments: u32) -> Self {
+    self.cross_segments = cross_segments.max(1);
+    self
+  }
+
+  /// Set the flow speed on flat ground and the extra speed from the slope.
+  pub fn speed(mut self, base_speed: f32, slope_speed: f32) -> Self {
+    self.base_speed = base_speed;
+    self.slope_speed = slope_speed;
+    self
+  }
+
+  /// Build the river center line.
+  pub fn build_river(&self) -> River {
+    let count = self.center.len();
+    let mut distance = 0.0;
+    let points = (0..count)
+      .map(|i| {
+        let prev = self.center[i.saturating_sub(1)];
+        let next = self.center[(i + 1).min(count - 1)];
+        let position = self.center[i];
+        if i > 0 {
+          distance += (position - prev).length();
+        }
+        let tangent = next - prev;
+        let run = tangent.xz().length();
+        // Water flows faster down steeper slopes.
+        let slope = if run > 0.0 {
+          (-tangent.y / run).max(0.0)
+        } else {
+          0.0
+        };
+        let flat = Vec3::new(tangent.x, 0.0, tangent.z).normalize_or(Vec3::Z);
+        RiverPoint {
+          position,
+          right: flat.cross(Vec3::Y),
+          width: self.widths[i],
+          distance,
+          speed: self.base_speed + self.slope_speed * slope,
+        }
+      })
+      .collect();
+    River { points }
+  }
+
+  /// Build the ribbon mesh.  UVs are in world units (across, along) the river.
+  pub fn build_mesh(&self, river: &River) -> Mesh {
+    let cols = self.cross_segments + 1;
+    let mut positions = Vec::new();
+    let mut uvs = Vec::new();
+    for point in river.points() {
+      for col in 0..cols {
+        let lateral = (col as f32 / self.cross_segments as f32 - 0.5) * point.width;
+        positions.push((point.position + point.right * lateral).to_array());
+        uvs.push([lateral, point.distance]);
+      }
+    }
+    let normals = vec![[0.0, 1.0, 0.0]; positions.len()];
+
+    let rows = river.points().len().saturating_sub(1) as u32;
+    let mut indices = Vec::with_capacity((rows * self.cross_segments * 6) as usize);
+    for row in 0..rows {
+      for col in 0..self.cross_segments {
+        let a = row * cols + col;
+        let b = a + 1;
+        let c = a + cols;
+        let d = c + 1;
+        indices.extend_from_slice(&[a, b, c, b, d, c]);
+      }
+    }
+
+    Mesh::new(
+      PrimitiveTopology::TriangleList,
+      RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(indices))
+  }
+
+  /// Build the flow of the river.  The flow map encodes the speed along the river.
+  pub fn build_flow(&self, river: &River, images: &mut Assets<Image>) -> WaterFlow {
+    let points = river.points();
+    let max_speed = points
+      .iter()
+      .fold(0.0_f32, |max, p| max.max(p.speed))
+      .max(0.0001);
+    let max_width = points.iter().fold(0.0_f32, |max, p| max.max(p.width));
+    // The waves flow along the `y` wave coordinate.  The samples aren't evenly
+    // spaced, so the texels are resampled by distance along the river.
+    let texels = points.len().max(1);
+    let data = (0..texels)
+      .flat_map(|i| {
+        let distance = (i as f32 + 0.5) / texels as f32 * river.length();
+        let speed = river.point_at(distance).map_or(0.0, |p| p.speed);
+        let v = 0.5 + 0.5 * (speed / max_speed).clamp(-1.0, 1.0);
+        [128, (v * 255.0).round() as u8, 0, 255]
+      })
+      .collect();
+    let image = Image::new(
+      Extent3d {
+        width: 1,
+        height: texels as u32,
+        depth_or_array_layers: 1,
+      },
+      TextureDimension::D2,
+      data,
+      TextureFormat::Rgba8Unorm,
+      RenderAssetUsages::default(),
+    );
+    let rect = Rect::new(-max_width / 2.0, 0.0, max_width / 2.0, river.length());
+    WaterFlow::from_flow_map(images.add(image), rect, max_speed)
+  }
+
+  /// Spawn the river water body with a water material from `settings`.
+  pub fn spawn(
+    &self,
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    images: &mut Assets<Image>,
+    materials: &mut Assets<StandardWaterMaterial>,
+    settings: &WaterSettings,
+  ) -> Entity {
+    let river = self.build_river();
+    let mesh = meshes.add(self.build_mesh(&river));
+    let flow = self.build_flow(&river, images);
+
+    let mut material = settings.new_material(Vec2::ZERO, Vec2::ONE);
+    // Waves travel down the river.
+    material.extension.wave_dir_a = Vec2::Y;
+    material.extension.wave_dir_b = Vec2::Y;
+    flow.apply_to(&mut material.extension);
+
+    commands
+      .spawn((
+        Name::new("River"),
+        river,
+        flow,
+        Mesh3d(mesh),
+        MeshMaterial3d(materials.add(material)),
+        Transform::default(),
+        Visibility::default(),
+        NotShadowCaster,
+      ))
+      .id()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn flow_map_matches_projection() {
+    // Dense samples near the source, sparse ones downstream, with a steep middle section.
+    let builder = RiverBuilder::from_points(vec![
+      Vec3::new(0.0, 0.0, 0.0),
+      Vec3::new(0.0, 0.0, 1.0),
+      Vec3::new(0.0, 0.0, 2.0),
+      Vec3::new(0.0, -2.0, 12.0),
+      Vec3::new(0.0, -2.5, 30.0),
+      Vec3::new(0.0, -2.5, 60.0),
+    ]);
+    let river = builder.build_river();
+    let mut images = Assets::<Image>::default();
+    let flow = builder.build_flow(&river, &mut images);
+
+    let texels = river.points().len();
+    for i in 0..texels {
+      let distance = (i as f32 + 0.5) / texels as f32 * river.length();
+      let expected = river
+        .project(Vec3::new(0.0, 0.0, distance))
+        .unwrap()
+        .point
+        .speed;
+      let speed = flow.velocity_at(Some(&images), Vec2::new(0.0, distance)).y;
+      assert!(
+        (speed - expected).abs() < flow.flow_map_scale * 0.02,
+        "distance {distance}: flow map speed {speed}, expected {expected}"
+      );
+    }
+  }
+}
@@ -0,0 +1,369 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{GlobalWaveState, WaterPresetHandle, WaterSettings, WaterTimeline, WaveDirection};
+
+/// Compact state of the global water for network replication.
+///
+/// The server sends snapshots from `WaterSnapshot::capture`, clients pass them to
+/// `WaterReconcile::receive` to smoothly follow the server's wave clock and wave
+/// direction crossfade.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct WaterSnapshot {
+  /// Wave time (see `GlobalWaveState::wave_time`).
+  pub wave_time: f32,
+  /// Speed of the wave animation.
+  pub wave_speed: f32,
+  /// Wave direction A (fading out).
+  pub dir_a: Vec2,
+  /// Wave direction B (fading in).
+  pub dir_b: Vec2,
+  /// Wave direction crossfade: 0 = fully A, 1 = fully B.
+  pub blend: f32,
+  /// Duration of the wave direction crossfade in seconds.
+  pub blend_duration: f32,
+  /// Hash of the `WaterSettings` (see `water_settings_hash`).
+  pub settings_hash: u64,
+}
+
+impl WaterSnapshot {
+  /// Capture the global water state.
+  pub fn capture(wave_state: &GlobalWaveState, settings: &WaterSettings) -> Self {
+    Self {
+      wave_time: wave_state.wave_time,
+      wave_speed: wave_state.wave_speed,
+      dir_a: wave_state.dir_a,
+      dir_b: wave_state.dir_b,
+      blend: wave_state.blend,
+      blend_duration: settings.wave_direction_blend_duration,
+      settings_hash: water_settings_hash(settings),
+    }
+  }
+
+  /// The state `dt` seconds later.
+  pub fn extrapolate(&self, dt: f32) -> Self {
+    let blend = if self.blend_duration > 0.0 {
+      (self.blend + dt / self.blend_duration).min(1.0)
+    } else {
+      1.0
+    };
+    Self {
+      wave_time: self.wave_time + dt * self.wave_speed,
+      blend,
+      ..*self
+    }
+  }
+}
+
+/// FNV-1a hash, stable across platforms and builds.
+struct StableHasher(u64);
+
+impl StableHasher {
+  fn new() -> Self {
+    Self(0xcbf2_9ce4_8422_2325)
+  }
+
+  fn write(&mut self, bytes: &[u8]) {
+    for byte in bytes {
+      self.0 ^= *byte as u64;
+      self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+    }
+  }
+
+  fn f32(&mut self, value: f32) {
+    self.write(&value.to_bits().to_le_bytes());
+  }
+
+  fn color(&mut self, color: Color) {
+    for value in color.to_linear().to_f32_array() {
+      self.f32(value);
+    }
+  }
+}
+
+/// Hash of the `WaterSettings` that affect the waves and look of the water.
+///
+/// Clients can compare it with the hash of a `WaterSnapshot` to detect settings that
+/// weren't replicated.  The `height` isn't included, it's usually driven by a `Tide`.
+pub fn water_settings_hash(settings: &WaterSettings) -> u64 {
+  let mut hasher = StableHasher::new();
+  for value in [
+    settings.amplitude,
+    settings.clarity,
+    settings.edge_scale,
+    settings.wave_direction.x,
+    settings.wave_direction.y,
+    settings.wave_direction_blend_duration,
+    settings.refraction_strength,
+    settings.refraction_dispersion,
+    settings.subsurface_strength,
+    settings.detail_normal_strength,
+    settings.detail_normal_scale.x,
+    settings.detail_normal_scale.y,
+    settings.detail_normal_speed,
+    settings.detail_fade_distance,
+    settings.flow.velocity.x,
+    settings.flow.velocity.y,
+    settings.flow.flow_map_scale,
+    settings.flow.period,
+    settings.wave_length,
+    settings.wave_speed,
+    settings.rain_intensity,
+    settings.rain_ripple_size,
+    settings.rain_ripple_strength,
+  ] {
+    hasher.f32(value);
+  }
+  for color in [
+    settings.base_color,
+    settings.deep_color,
+    settings.shallow_color,
+    settings.edge_color,
+    settings.subsurface_color,
+  ] {
+    hasher.color(color);
+  }
+  let quality: u32 = settings.water_quality.into();
+  hasher.write(&quality.to_le_bytes());
+  hasher.write(&[settings.refraction as u8]);
+  hasher.0
+}
+
+/// Reconcile the local water with authoritative `WaterSnapshot`s (multiplayer clients).
+///
+/// Insert this resource on clients and pass the snapshots from the server to
+/// `receive`.  Small differences of the wave clock and the wave direction crossfade
+/// are corrected gradually, large differences (e.g. on join) are snapped.
+#[derive(Resource, Clone, Debug, Reflect)]
+#[reflect(Resource, Default)]
+pub struct WaterReconcile {
+  /// Fraction of the remaining difference corrected per second.
+  pub correction_rate: f32,
+  /// Snap the wave clock when it's off by more than this many seconds of wave time.
+  pub snap_threshold: f32,
+  /// The latest snapshot, extrapolated to the current time.
+  target: Option<WaterSnapshot>,
+  /// The snapshot's `settings_hash` doesn't match the local `WaterSettings`.
+  settings_mismatch: bool,
+}
+
+impl Default for WaterReconcile {
+  fn default() -> Self {
+    Self {
+      correction_rate: 2.0,
+      snap_threshold: 1.0,
+      target: None,
+      settings_mismatch: false,
+    }
+  }
+}
+
+impl WaterReconcile {
+  /// Reconcile with a snapshot that was captured `latency` seconds ago.
+  pub fn receive(&mut self, snapshot: WaterSnapshot, latency: f32) {
+    self.target = Some(snapshot.extrapolate(latency.max(0.0)));
+  }
+
+  /// The latest snapshot, extrapolated to the current time.
+  pub fn target(&self) -> Option<&WaterSnapshot> {
+    self.target.as_ref()
+  }
+
+  /// Returns true if the server's `WaterSettings` differ from the local settings.
+  pub fn is_settings_mismatch(&self) -> bool {
+    self.settings_mismatch
+  }
+}
+
+/// Correct the local wave clock and wave directions towards the `WaterReconcile` target.
+pub fn reconcile_water(
+  time: Res<Time>,
+  settings: Res<WaterSettings>,
+  mut reconcile: ResMut<WaterReconcile>,
+  mut wave_state: ResMut<GlobalWaveState>,
+  mut wave_dirs: Query<&mut WaveDirection, (Without<WaterPresetHandle>, Without<WaterTimeline>)>,
+) {
+  let dt = time.delta_secs();
+  let now = time.elapsed_secs_wrapped();
+  let Some(target) = reconcile.target else {
+    return;
+  };
+  // Keep following the server between snapshots.
+  let target = target.extrapolate(dt);
+  reconcile.target = Some(target);
+  let mismatch = target.settings_hash != water_settings_hash(&settings);
+  if reconcile.settings_mismatch != mismatch {
+    reconcile.settings_mismatch = mismatch;
+  }
+  let correction = 1.0 - (-reconcile.correction_rate * dt).exp();
+
+  // Wave clock.
+  if wave_state.wave_speed != target.wave_speed {
+    wave_state.set_wave_speed(now, target.wave_speed);
+  }
+  let error = target.wave_time - wave_state.wave_time(now);
+  if error.abs() > reconcile.snap_threshold {
+    wave_state.time_offset += error;
+  } else if error.abs() > 0.0001 {
+    wave_state.time_offset += error * correction;
+  }
+
+  // Wave direction crossfade.
+  for mut wave_dir in wave_dirs.iter_mut() {
+    let same_dirs = wave_dir.dir_a().distance_squared(target.dir_a) < 0.0001
+      && wave_dir.dir_b().distance_squared(target.dir_b) < 0.0001;
+    if !same_dirs {
+      // Missed the start of a crossfade.
+      wave_dir.set_crossfade(target.dir_a, target.dir_b, target.blend);
+      wave_dir.set_duration(target.blend_duration);
+    } else if (wave_dir.blend() - target.blend).abs() > 0.0001 {
+      let blend = wave_dir.blend() + (target.blend - wave_dir.blend()) * correction;
+      wave_dir.set_crossfade(target.dir_a, target.dir_b, blend);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use bevy::time::TimeUpdateStrategy;
+
+  use super::*;
+  use crate::{
+    sync_wave_direction_settings, update_global_wave_state, update_wave_clock,
+    update_wave_direction, WaterTile, WaveDirectionChanged, WaveDirectionSettled,
+  };
+
+  /// Server frame time.
+  const DT: f32 = 1.0 / 60.0;
+
+  /// The wave clock, direction and snapshot systems of `WaterPlugin`, with a fixed frame time.
+  fn water_app(dt: f32) -> App {
+    let mut app = App::new();
+    app
+      .add_plugins(MinimalPlugins)
+      .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+        dt,
+      )))
+      .init_resource::<WaterSettings>()
+      .init_resource::<GlobalWaveState>()
+      .add_message::<WaveDirectionChanged>()
+      .add_message::<WaveDirectionSettled>()
+      .add_systems(
+        Update,
+        (
+          update_wave_clock,
+          sync_wave_direction_settings.run_if(resource_changed::<WaterSettings>),
+          update_wave_direction,
+          reconcile_water.run_if(resource_exists::<WaterReconcile>),
+          update_global_wave_state,
+        )
+          .chain(),
+      );
+    let settings = app.world().resource::<WaterSettings>().clone();
+    app.world_mut().spawn((
+      WaterTile::default(),
+      WaveDirection::with_duration(
+        settings.wave_direction,
+        settings.wave_direction_blend_duration,
+      ),
+    ));
+    app
+  }
+
+  fn client_wave_time(app: &App) -> f32 {
+    let now = app.world().resource::<Time>().elapsed_secs_wrapped();
+    app.world().resource::<GlobalWaveState>().wave_time(now)
+  }
+
+  fn wave_dir(app: &mut App) -> WaveDirection {
+    *app
+      .world_mut()
+      .query::<&WaveDirection>()
+      .single(app.world())
+      .unwrap()
+  }
+
+  #[test]
+  fn client_reconciles_with_server() {
+    let mut server = water_app(DT);
+    // The client joins two seconds later and its clock runs 2% fast.
+    for _ in 0..120 {
+      server.update();
+    }
+    let mut client = water_app(DT * 1.02);
+    client.init_resource::<WaterReconcile>();
+    client.update();
+
+    let mut last_client_time = None;
+    let mut in_flight = None;
+    for frame in 0..600 {
+      if frame == 60 {
+        // Storm from the north, the client doesn't have the new settings yet.
+        server
+          .world_mut()
+          .resource_mut::<WaterSettings>()
+          .wave_direction = Vec2::Y;
+      }
+      server.update();
+      let snapshot = WaterSnapshot::capture(
+        server.world().resource::<GlobalWaveState>(),
+        server.world().resource::<WaterSettings>(),
+      );
+      // Received one frame later.
+      if let Some(received) = in_flight.replace(snapshot) {
+        client
+          .world_mut()
+          .resource_mut::<WaterReconcile>()
+          .receive(received, DT);
+      }
+      client.update();
+      // The client frame ends one server frame later.
+      let server_time = snapshot.extrapolate(DT).wave_time;
+
+      if frame == 0 {
+        continue;
+      }
+      // Snapped to the server on the first snapshot.
+      let client_time = client_wave_time(&client);
+      if let Some(last) = last_client_time {
+        // No pops after the initial snap.
+        let step: f32 = client_time - last;
+        assert!(
+          (step - DT).abs() < 0.01,
+          "frame {frame}: wave time step {step}"
+        );
+      }
+      last_client_time = Some(client_time);
+      let error = (client_time - server_time).abs();
+      assert!(error < 0.01, "frame {frame}: wave time error {error}");
+
+      let mismatch = client
+        .world()
+        .resource::<WaterReconcile>()
+        .is_settings_mismatch();
+      assert_eq!(mismatch, (61..=120).contains(&frame), "frame {frame}");
+      if frame == 120 {
+        // Replicate the settings.
+        let settings = server.world().resource::<WaterSettings>().clone();
+        client.insert_resource(settings);
+      }
+
+      // Follow the server's crossfade, also while it's running.
+      let (server_dir, client_dir) = (wave_dir(&mut server), wave_dir(&mut client));
+      if frame > 60 {
+        assert_eq!(client_dir.dir_b(), server_dir.dir_b());
+        let blend_error = (client_dir.blend() - server_dir.blend()).abs();
+        assert!(
+          blend_error < 0.02,
+          "frame {frame}: blend error {blend_error}"
+        );
+      }
+    }
+    let (server_dir, client_dir) = (wave_dir(&mut server), wave_dir(&mut client));
+    assert_eq!(server_dir.blend(), 1.0);
+    assert_eq!(client_dir.blend(), 1.0);
+    assert_eq!(client_dir.dir_b(), Vec2::Y);
+  }
+}
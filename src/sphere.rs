@@ -0,0 +1,68 @@
+use bevy::prelude::*;
+
+use crate::material::StandardWaterMaterial;
+
+/// Spherical water (planet oceans).
+///
+/// Add this component to a water mesh entity (usually a sphere mesh centered on the entity).
+/// The water shader then uses seamless triplanar wave coordinates and displaces the
+/// surface away from the entity's position, instead of using the mesh UVs.
+///
+/// Use `WaterParam::sphere_wave_height` or `WaterBodies::sphere_surface` to float objects on the planet.
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[reflect(Component, Default)]
+pub struct WaterSphere {
+  /// Radius of the calm water surface (before the entity's scale).
+  pub radius: f32,
+}
+
+impl Default for WaterSphere {
+  fn default() -> Self {
+    Self { radius: 10.0 }
+  }
+}
+
+impl WaterSphere {
+  /// Spherical water with the calm surface at `radius`.
+  pub fn new(radius: f32) -> Self {
+    Self { radius }
+  }
+
+  /// Radius of the calm water surface in world units.
+  pub fn global_radius(&self, transform: &GlobalTransform) -> f32 {
+    self.radius * transform.scale().max_element()
+  }
+}
+
+/// A point on the surface of spherical water.
+#[derive(Clone, Copy, Debug)]
+pub struct WaterSphereSurface {
+  /// The water sphere entity.
+  pub body: Entity,
+  /// Global position of the water surface (including waves).
+  pub point: Vec3,
+  /// Direction away from the planet center.
+  pub up: Vec3,
+  /// Depth of the queried position below the water surface (negative above the surface).
+  pub depth: f32,
+}
+
+type WaterSphereQuery = (
+  &'static WaterSphere,
+  &'static GlobalTransform,
+  &'static MeshMaterial3d<StandardWaterMaterial>,
+);
+type WaterSphereChanged = Or<(Changed<WaterSphere>, Changed<GlobalTransform>)>;
+
+/// Apply `WaterSphere` components to their water materials.
+pub fn apply_water_sphere(
+  mut materials: ResMut<Assets<StandardWaterMaterial>>,
+  spheres: Query<WaterSphereQuery, WaterSphereChanged>,
+) {
+  for (sphere, transform, material_handle) in spheres.iter() {
+    if let Some(mat) = materials.get_mut(&material_handle.0) {
+      mat.extension.sphere_center = transform.translation();
+      mat.extension.sphere_radius = sphere.global_radius(transform);
+    }
+  }
+}
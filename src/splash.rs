@@ -0,0 +1,305 @@
+use bevy::{light::NotShadowCaster, math::Vec3Swizzles, prelude::*};
+
+use crate::{Wake, WaterBodies, WaterContact, WaterEntered, WaterExited, WaterSettings};
+
+/// Settings for the built-in splash and spray particles.
+///
+/// Insert this resource to enable the particles.  Splashes are spawned for
+/// `WaterEntered`/`WaterExited` messages (see `WaterContact`), bow spray for moving
+/// `Wake` entities and spray from breaking wave crests around the camera.
+///
+/// The particles use the `edge_color` of the `WaterSettings` and are absorbed when
+/// they fall back into the water.
+#[derive(Resource, Clone, Debug, Reflect)]
+#[reflect(Resource, Default)]
+pub struct WaterSplashSettings {
+  /// Maximum number of live particles.
+  pub max_particles: usize,
+  /// Number of particles per unit of impact energy (`radius^3 * speed^2`).
+  pub particles_per_energy: f32,
+  /// Maximum number of particles for a single splash.
+  pub max_splash_particles: usize,
+  /// Size of the particles.
+  pub size: f32,
+  /// Maximum lifetime of the particles in seconds.
+  pub lifetime: f32,
+  /// Gravity pulling the particles down.
+  pub gravity: f32,
+  /// Air drag of the particles.
+  pub drag: f32,
+  /// Bow spray particles per second per unit of speed of `Wake` entities, 0.0 = disabled.
+  pub bow_spray: f32,
+  /// Crest spray particles per second around the camera, 0.0 = disabled.
+  pub crest_spray: f32,
+  /// Distance around the camera to spawn crest spray.
+  pub crest_spray_radius: f32,
+}
+
+impl Default for WaterSplashSettings {
+  fn default() -> Self {
+    Self {
+      max_particles: 1024,
+      particles_per_energy: 4.0,
+      max_splash_particles: 128,
+      size: 0.1,
+      lifetime: 2.0,
+      gravity: 9.81,
+      drag: 0.5,
+      bow_spray: 2.0,
+      crest_spray: 0.0,
+      crest_spray_radius: 40.0,
+    }
+  }
+}
+
+/// A splash or spray particle.
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[reflect(Component)]
+pub struct SplashParticle {
+  pub velocity: Vec3,
+  pub age: f32,
+  pub lifetime: f32,
+  pub size: f32,
+}
+
+/// Shared particle assets and random state.
+#[derive(Default)]
+pub struct SplashState {
+  mesh: Option<Handle<Mesh>>,
+  material: Option<Handle<StandardMaterial>>,
+  seed: u32,
+  crest_accumulator: f32,
+}
+
+impl SplashState {
+  /// Random value in 0.0..1.0 (xorshift).
+  fn random(&mut self) -> f32 {
+    let mut x = self.seed.max(1);
+    x ^= x << 13;
+    x ^= x >> 17;
+    x ^= x << 5;
+    self.seed = x;
+    (x >> 8) as f32 / (1u32 << 24) as f32
+  }
+
+  /// Random unit direction on the X/Z plane.
+  fn random_dir(&mut self) -> Vec2 {
+    Vec2::from_angle(self.random() * std::f32::consts::TAU)
+  }
+}
+
+/// Spawns particles, keeps track of the number of live particles.
+struct SplashSpawner<'a, 'w, 's> {
+  commands: &'a mut Commands<'w, 's>,
+  settings: &'a WaterSplashSettings,
+  mesh: Handle<Mesh>,
+  material: Handle<StandardMaterial>,
+  count: usize,
+}
+
+impl SplashSpawner<'_, '_, '_> {
+  fn spawn(&mut self, state: &mut SplashState, position: Vec3, velocity: Vec3) {
+    if self.count >= self.settings.max_particles {
+      return;
+    }
+    self.count += 1;
+    let size = self.settings.size * (0.5 + state.random());
+    self.commands.spawn((
+      Name::new("Splash particle"),
+      SplashParticle {
+        velocity,
+        age: 0.0,
+        lifetime: self.settings.lifetime * (0.5 + 0.5 * state.random()),
+        size,
+      },
+      Mesh3d(self.mesh.clone()),
+      MeshMaterial3d(self.material.clone()),
+      Transform::from_translation(position).with_scale(Vec3::splat(size)),
+      NotShadowCaster,
+    ));
+  }
+
+  /// A crown of particles thrown up and out from `position`.
+  fn splash(
+    &mut self,
+    state: &mut SplashState,
+    position: Vec3,
+    normal: Vec3,
+    speed: f32,
+    count: usize,
+  ) {
+    let count = count.min(self.settings.max_splash_particles);
+    for _ in 0..count {
+      let out = state.random_dir();
+      let spread = speed * (0.2 + 0.3 * state.random());
+      let up = speed * (0.3 + 0.5 * state.random());
+      let velocity = Vec3::new(out.x, 0.0, out.y) * spread + normal * up;
+      self.spawn(state, position, velocity);
+    }
+  }
+}
+
+/// Edge color of the water, used for the particles.
+fn splash_color(settings: &WaterSettings) -> Color {
+  settings.edge_color.with_alpha(0.8)
+}
+
+type SplashContactQuery = (&'static WaterContact, &'static GlobalTransform);
+
+/// Spawn splash and spray particles.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_water_splashes(
+  mut commands: Commands,
+  time: Res<Time>,
+  settings: Res<WaterSplashSettings>,
+  bodies: WaterBodies,
+  mut state: Local<SplashState>,
+  mut meshes: ResMut<Assets<Mesh>>,
+  mut materials: ResMut<Assets<StandardMaterial>>,
+  mut entered: MessageReader<WaterEntered>,
+  mut exited: MessageReader<WaterExited>,
+  contacts: Query<SplashContactQuery>,
+  wakes: Query<(&Wake, &GlobalTransform)>,
+  cameras: Query<&GlobalTransform, With<Camera3d>>,
+  particles: Query<(), With<SplashParticle>>,
+) {
+  let water_settings = &bodies.water.settings;
+  let mesh = state
+    .mesh
+    .get_or_insert_with(|| meshes.add(Sphere::new(0.5).mesh().ico(1).unwrap()))
+    .clone();
+  let material = state
+    .material
+    .get_or_insert_with(|| {
+      materials.add(StandardMaterial {
+        base_color: splash_color(water_settings),
+        alpha_mode: AlphaMode::Blend,
+        perceptual_roughness: 0.3,
+        ..default()
+      })
+    })
+    .clone();
+  // Match the water's edge color.
+  if water_settings.is_changed() {
+    if let Some(mat) = materials.get_mut(&material) {
+      mat.base_color = splash_color(water_settings);
+    }
+  }
+
+  let mut spawner = SplashSpawner {
+    commands: &mut commands,
+    settings: &settings,
+    mesh,
+    material,
+    count: particles.iter().count(),
+  };
+  let dt = time.delta_secs();
+
+  // Splashes from entities hitting or leaving the water.
+  let impacts = entered
+    .read()
+    .map(|event| {
+      (
+        event.entity,
+        event.position,
+        event.normal,
+        event.impact_speed(),
+      )
+    })
+    .chain(exited.read().map(|event| {
+      let speed = event.velocity.dot(event.normal).max(0.0) * 0.5;
+      (event.entity, event.position, event.normal, speed)
+    }))
+    .collect::<Vec<_>>();
+  for (entity, position, normal, speed) in impacts {
+    let radius = contacts
+      .get(entity)
+      .map(|(contact, _)| contact.radius)
+      .unwrap_or(0.5);
+    let energy = radius.powi(3) * speed * speed;
+    let count = (energy * settings.particles_per_energy).round() as usize;
+    spawner.splash(&mut state, position, normal, speed, count);
+  }
+
+  // Bow spray from moving boats.
+  if settings.bow_spray > 0.0 {
+    for (wake, transform) in wakes.iter() {
+      let velocity = wake.velocity();
+      let speed = velocity.length();
+      if speed < 1.0 {
+        continue;
+      }
+      let forward = velocity / speed;
+      let bow = transform.translation().xz() + forward * wake.hull_length * 0.5;
+      let Some(height) = bodies.wave_height(Vec3::new(bow.x, 0.0, bow.y)) else {
+        continue;
+      };
+      let rate = settings.bow_spray * speed * dt;
+      let count = rate.floor() as usize + usize::from(state.random() < rate.fract());
+      for _ in 0..count {
+        let side = forward.perp() * if state.random() < 0.5 { -1.0 } else { 1.0 };
+        let out = (side * (0.3 + 0.3 * state.random()) + forward * 0.5) * speed;
+        let velocity = Vec3::new(out.x, speed * (0.2 + 0.3 * state.random()), out.y);
+        let position =
+          Vec3::new(bow.x, height, bow.y) + Vec3::new(side.x, 0.0, side.y) * wake.hull_width * 0.25;
+        spawner.spawn(&mut state, position, velocity);
+      }
+    }
+  }
+
+  // Spray blown off the wave crests around the camera.
+  let amplitude = water_settings.amplitude;
+  if settings.crest_spray > 0.0 && amplitude > 0.0 {
+    if let Some(camera) = cameras.iter().next() {
+      let center = camera.translation().xz();
+      let wind = water_settings.wave_direction.normalize_or_zero();
+      state.crest_accumulator += settings.crest_spray * dt;
+      while state.crest_accumulator >= 1.0 {
+        state.crest_accumulator -= 1.0;
+        let offset = state.random_dir() * state.random().sqrt() * settings.crest_spray_radius;
+        let p = center + offset;
+        let Some(surface) = bodies.surface(Vec3::new(p.x, 0.0, p.y)) else {
+          continue;
+        };
+        // Only the top of the waves.
+        let crest = (surface.height - bodies.water.height()) / amplitude;
+        if crest < 0.6 {
+          continue;
+        }
+        let out = wind * amplitude * (1.0 + state.random()) + state.random_dir() * 0.5;
+        let velocity = Vec3::new(out.x, amplitude * (1.0 + state.random()), out.y);
+        spawner.spawn(&mut state, Vec3::new(p.x, surface.height, p.y), velocity);
+      }
+    }
+  }
+}
+
+/// Move the splash particles and remove them when they fall back into the water.
+pub fn update_water_splashes(
+  mut commands: Commands,
+  time: Res<Time>,
+  settings: Res<WaterSplashSettings>,
+  bodies: WaterBodies,
+  mut particles: Query<(Entity, &mut SplashParticle, &mut Transform)>,
+) {
+  let dt = time.delta_secs();
+  for (entity, mut particle, mut transform) in particles.iter_mut() {
+    particle.age += dt;
+    particle.velocity.y -= settings.gravity * dt;
+    particle.velocity *= (-settings.drag * dt).exp();
+    transform.translation += particle.velocity * dt;
+
+    // Absorbed by the water when falling back into it.
+    let absorbed = particle.velocity.y < 0.0
+      && bodies
+        .wave_height(transform.translation)
+        .is_some_and(|height| transform.translation.y < height);
+    if absorbed || particle.age >= particle.lifetime {
+      commands.entity(entity).despawn();
+      continue;
+    }
+    // Shrink at the end of the lifetime.
+    let life = 1.0 - particle.age / particle.lifetime;
+    transform.scale = Vec3::splat(particle.size * life.sqrt());
+  }
+}
@@ -0,0 +1,292 @@
+use bevy::prelude::*;
+
+use crate::water::WaterTile;
+
+/// A harmonic tide constituent.
+#[derive(Clone, Copy, Debug, Reflect)]
+pub struct TideConstituent {
+  /// Amplitude in world units.
+  pub amplitude: f32,
+  /// Period in seconds (of tide time).
+  pub period: f32,
+  /// Phase in radians.
+  pub phase: f32,
+}
+
+impl TideConstituent {
+  /// Hours of tide time in seconds.
+  const HOUR: f32 = 3600.0;
+
+  pub fn new(amplitude: f32, period: f32, phase: f32) -> Self {
+    Self {
+      amplitude,
+      period,
+      phase,
+    }
+  }
+
+  /// Principal lunar semi-diurnal constituent (12.42 hours).
+  pub fn m2(amplitude: f32) -> Self {
+    Self::new(amplitude, 12.4206 * Self::HOUR, 0.0)
+  }
+
+  /// Principal solar semi-diurnal constituent (12 hours).
+  pub fn s2(amplitude: f32) -> Self {
+    Self::new(amplitude, 12.0 * Self::HOUR, 0.0)
+  }
+
+  /// Lunar diurnal constituent (23.93 hours).
+  pub fn k1(amplitude: f32) -> Self {
+    Self::new(amplitude, 23.9345 * Self::HOUR, 0.0)
+  }
+
+  /// Lunar diurnal constituent (25.82 hours).
+  pub fn o1(amplitude: f32) -> Self {
+    Self::new(amplitude, 25.8193 * Self::HOUR, 0.0)
+  }
+
+  /// Level of this constituent at `time`.
+  pub fn level_at(&self, time: f32) -> f32 {
+    if self.period <= 0.0 {
+      return 0.0;
+    }
+    self.amplitude * (time / self.period * std::f32::consts::TAU + self.phase).cos()
+  }
+}
+
+/// A scripted tide level offset.
+#[derive(Clone, Copy, Debug, Reflect)]
+pub struct TideKeyframe {
+  /// Tide time in seconds.
+  pub time: f32,
+  /// Level offset in world units.
+  pub level: f32,
+}
+
+/// Tide model driving the water level.
+///
+/// The level is the sum of the harmonic constituents plus the scripted keyframes
+/// (smoothly interpolated, holding the first/last value outside of their range).
+///
+/// As a resource the tide moves the water tiles and the height of `WaterParam`, the
+/// tide height replaces `WaterSettings::height` (which isn't changed, so the water
+/// materials aren't updated every frame).  As a component the tide moves the water body entity
+/// (lakes, rivers) up and down, reflected in `WaterBodies`.
+///
+/// Sends `HighTide` and `LowTide` messages when the level turns.
+#[derive(Resource, Component, Clone, Debug, Reflect)]
+#[reflect(Resource, Component, Default)]
+pub struct Tide {
+  /// Mean water height (the global water height or the entity's `Transform` height).
+  pub mean_height: f32,
+  /// Harmonic constituents.
+  pub constituents: Vec<TideConstituent>,
+  /// Scripted level offsets, sorted by time.
+  pub keyframes: Vec<TideKeyframe>,
+  /// Tide seconds per second, speeds up the tide for games.
+  pub time_scale: f32,
+  /// Current tide time in seconds.
+  pub time: f32,
+  /// Last level and if it was rising.
+  last: Option<(f32, Option<bool>)>,
+}
+
+impl Default for Tide {
+  fn default() -> Self {
+    Self::sine(1.0, 1.0, 120.0)
+  }
+}
+
+impl Tide {
+  /// A tide with the given constituents around `mean_height`.
+  pub fn harmonic(
+    mean_height: f32,
+    constituents: impl IntoIterator<Item = TideConstituent>,
+  ) -> Self {
+    Self {
+      mean_height,
+      constituents: constituents.into_iter().collect(),
+      keyframes: Vec::new(),
+      time_scale: 1.0,
+      time: 0.0,
+      last: None,
+    }
+  }
+
+  /// A simple sine tide with `amplitude` and `period` in seconds around `mean_height`.
+  pub fn sine(mean_height: f32, amplitude: f32, period: f32) -> Self {
+    Self::harmonic(mean_height, [TideConstituent::new(amplitude, period, 0.0)])
+  }
+
+  /// A tide that only follows the scripted keyframes.
+  pub fn scripted(mean_height: f32, keyframes: impl IntoIterator<Item = TideKeyframe>) -> Self {
+    Self::harmonic(mean_height, []).with_keyframes(keyframes)
+  }
+
+  /// Set the scripted keyframes.
+  pub fn with_keyframes(mut self, keyframes: impl IntoIterator<Item = TideKeyframe>) -> Self {
+    self.keyframes = keyframes.into_iter().collect();
+    self.keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+    self
+  }
+
+  /// Set the tide seconds per second.
+  pub fn with_time_scale(mut self, time_scale: f32) -> Self {
+    self.time_scale = time_scale;
+    self
+  }
+
+  /// Tide level offset at tide `time`.
+  pub fn level_at(&self, time: f32) -> f32 {
+    let harmonic: f32 = self.constituents.iter().map(|c| c.level_at(time)).sum();
+    harmonic + self.keyframe_level(time)
+  }
+
+  /// Current tide level offset.
+  pub fn level(&self) -> f32 {
+    self.level_at(self.time)
+  }
+
+  /// Current water height.
+  pub fn height(&self) -> f32 {
+    self.mean_height + self.level()
+  }
+
+  fn keyframe_level(&self, time: f32) -> f32 {
+    let (Some(first), Some(last)) = (self.keyframes.first(), self.keyframes.last()) else {
+      return 0.0;
+    };
+    if time <= first.time {
+      return first.level;
+    }
+    if time >= last.time {
+      return last.level;
+    }
+    let next = self.keyframes.partition_point(|k| k.time <= time);
+    let (a, b) = (self.keyframes[next - 1], self.keyframes[next]);
+    let t = (time - a.time) / (b.time - a.time).max(f32::EPSILON);
+    let t = t * t * (3.0 - 2.0 * t);
+    a.level + (b.level - a.level) * t
+  }
+
+  /// Advance the tide by `dt` seconds, returns `Some(true)` at high tide and
+  /// `Some(false)` at low tide.
+  pub fn update(&mut self, dt: f32) -> Option<bool> {
+    self.time += dt * self.time_scale;
+    let level = self.level();
+    let (last_level, last_rising) = self.last.unwrap_or((level, None));
+    let rising = if level > last_level {
+      Some(true)
+    } else if level < last_level {
+      Some(false)
+    } else {
+      last_rising
+    };
+    self.last = Some((level, rising));
+    match (last_rising, rising) {
+      (Some(true), Some(false)) => Some(true),
+      (Some(false), Some(true)) => Some(false),
+      _ => None,
+    }
+  }
+}
+
+/// Sent at high tide.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct HighTide {
+  /// The water body, `None` for the global tide.
+  pub body: Option<Entity>,
+  /// Water height at high tide.
+  pub height: f32,
+}
+
+/// Sent at low tide.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct LowTide {
+  /// The water body, `None` for the global tide.
+  pub body: Option<Entity>,
+  /// Water height at low tide.
+  pub height: f32,
+}
+
+fn send_tide_message(
+  turn: Option<bool>,
+  body: Option<Entity>,
+  height: f32,
+  high: &mut MessageWriter<HighTide>,
+  low: &mut MessageWriter<LowTide>,
+) {
+  match turn {
+    Some(true) => {
+      high.write(HighTide { body, height });
+    }
+    Some(false) => {
+      low.write(LowTide { body, height });
+    }
+    None => {}
+  }
+}
+
+/// Drive the global water height from the `Tide` resource.
+pub fn update_global_tide(
+  time: Res<Time>,
+  mut tide: ResMut<Tide>,
+  mut tiles: Query<&mut Transform, With<WaterTile>>,
+  mut high: MessageWriter<HighTide>,
+  mut low: MessageWriter<LowTide>,
+) {
+  let turn = tide.update(time.delta_secs());
+  let height = tide.height();
+  send_tide_message(turn, None, height, &mut high, &mut low);
+
+  // Move the tiles directly, the tide is already smooth.
+  for mut transform in tiles.iter_mut() {
+    if transform.translation.y != height {
+      transform.translation.y = height;
+    }
+  }
+}
+
+/// Drive the height of water bodies with a `Tide` component.
+pub fn update_tides(
+  time: Res<Time>,
+  mut bodies: Query<(Entity, &mut Tide, &mut Transform)>,
+  mut high: MessageWriter<HighTide>,
+  mut low: MessageWriter<LowTide>,
+) {
+  let dt = time.delta_secs();
+  for (entity, mut tide, mut transform) in bodies.iter_mut() {
+    let turn = tide.update(dt);
+    let height = tide.height();
+    send_tide_message(turn, Some(entity), height, &mut high, &mut low);
+    if transform.translation.y != height {
+      transform.translation.y = height;
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn one_high_and_low_tide_per_period() {
+    let mut tide = Tide::sine(2.0, 1.0, 60.0);
+    tide.time = 5.0;
+    let mut turns = Vec::new();
+    // One period in half second steps.
+    for _ in 0..120 {
+      if let Some(high) = tide.update(0.5) {
+        turns.push((high, tide.time, tide.height()));
+      }
+    }
+    assert_eq!(turns.len(), 2, "{turns:?}");
+    let (high, low) = (turns[1], turns[0]);
+    assert!(high.0 && !low.0);
+    // Sent on the first step past the turn.
+    assert_eq!(low.1, 30.5);
+    assert_eq!(high.1, 60.5);
+    assert!((low.2 - 1.0).abs() < 0.01);
+    assert!((high.2 - 3.0).abs() < 0.01);
+  }
+}
@@ -0,0 +1,634 @@
+use std::collections::HashMap;
+
+use bevy::{
+  asset::RenderAssetUsages,
+  light::NotShadowCaster,
+  mesh::{Indices, PrimitiveTopology},
+  prelude::*,
+};
+
+use crate::{material::StandardWaterMaterial, LakeBuilder, WaterSettings, WaveDirection};
+#[cfg(feature = "easings")]
+use crate::{Ease, WaterHeightEasingSettings};
+
+/// An enclosed body of water (tank, aquarium, flooded room) confined to a convex hull.
+///
+/// Spawn it with a `Transform`, the water surface and side walls are added as children.
+/// Changing `fill` moves the water level (eased when the `easings` feature is enabled).
+///
+/// Moving the level only moves the surface of prisms (vertical sides, like a cuboid).
+/// Other hulls rebuild the meshes while the level moves, since the cross-section changes.
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component, Default)]
+#[require(Transform, Visibility)]
+pub struct WaterVolume {
+  points: Vec<Vec3>,
+  bottom: f32,
+  top: f32,
+  /// Fill level from 0.0 (empty) to 1.0 (full).
+  pub fill: f32,
+  /// Wave amplitude inside the volume.
+  pub amplitude: f32,
+  /// Render the sides of the water body, for tanks viewed through glass.
+  pub side_walls: bool,
+  /// Maximum length of a triangle edge of the water surface.
+  pub resolution: f32,
+}
+
+impl Default for WaterVolume {
+  fn default() -> Self {
+    Self::cuboid(Vec3::ONE)
+  }
+}
+
+impl WaterVolume {
+  /// Create a box shaped volume centered on the entity.
+  pub fn cuboid(half_size: Vec3) -> Self {
+    let h = half_size.abs();
+    Self::convex_hull([
+      Vec3::new(-h.x, -h.y, -h.z),
+      Vec3::new(h.x, -h.y, -h.z),
+      Vec3::new(h.x, -h.y, h.z),
+      Vec3::new(-h.x, -h.y, h.z),
+      Vec3::new(-h.x, h.y, -h.z),
+      Vec3::new(h.x, h.y, -h.z),
+      Vec3::new(h.x, h.y, h.z),
+      Vec3::new(-h.x, h.y, h.z),
+    ])
+  }
+
+  /// Create a volume from the convex hull of `points` (local space).
+  pub fn convex_hull(points: impl IntoIterator<Item = Vec3>) -> Self {
+    let points: Vec<Vec3> = points.into_iter().collect();
+    let bottom = points.iter().map(|p| p.y).fold(f32::MAX, f32::min);
+    let top = points.iter().map(|p| p.y).fold(f32::MIN, f32::max);
+    Self {
+      points,
+      bottom,
+      top,
+      fill: 0.5,
+      amplitude: 0.05,
+      side_walls: true,
+      resolution: 1.0,
+    }
+  }
+
+  /// Set the fill level.
+  pub fn with_fill(mut self, fill: f32) -> Self {
+    self.fill = fill;
+    self
+  }
+
+  /// Set the wave amplitude.
+  pub fn with_amplitude(mut self, amplitude: f32) -> Self {
+    self.amplitude = amplitude;
+    self
+  }
+
+  /// Points of the convex hull.
+  pub fn points(&self) -> &[Vec3] {
+    &self.points
+  }
+
+  /// Local height of the bottom of the volume.
+  pub fn bottom(&self) -> f32 {
+    self.bottom
+  }
+
+  /// Local height of the top of the volume.
+  pub fn top(&self) -> f32 {
+    self.top
+  }
+
+  /// Local height of the water level for `fill`.
+  pub fn level(&self, fill: f32) -> f32 {
+    self.bottom.lerp(self.top, fill.clamp(0.0, 1.0))
+  }
+
+  /// Horizontal cross-section of the hull at the local `height` (counter-clockwise on the X/Z plane).
+  pub fn cross_section(&self, height: f32) -> Vec<Vec2> {
+    self
+      .section(height, false)
+      .into_iter()
+      .map(|(p, _)| p)
+      .collect()
+  }
+
+  /// Returns true if the hull has vertical sides, the cross-section is the same at every height.
+  pub fn is_prism(&self) -> bool {
+    self
+      .points
+      .iter()
+      .all(|p| p.y == self.bottom || p.y == self.top)
+      && same_section(
+        &self.cross_section(self.bottom),
+        &self.cross_section(self.top),
+      )
+  }
+
+  /// Returns true if the local point `p` is inside the hull.
+  pub fn contains(&self, p: Vec3) -> bool {
+    p.y >= self.bottom && p.y <= self.top && convex_contains(&self.cross_section(p.y), p.xz())
+  }
+
+  /// The cross-section is the convex hull of the intersections of the plane with
+  /// the segments between every pair of points on opposite sides of it.
+  ///
+  /// Each vertex is labeled with the pair of points it lies between.
+  /// With `strict` points on the plane are ignored, which keeps the labels stable
+  /// between the heights of the hull points.
+  fn section(&self, height: f32, strict: bool) -> Vec<(Vec2, (usize, usize))> {
+    let mut crossings = Vec::new();
+    for (i, a) in self.points.iter().enumerate() {
+      if !strict && a.y == height {
+        crossings.push((a.xz(), (i, i)));
+      }
+      for (j, b) in self.points.iter().enumerate().skip(i + 1) {
+        if (a.y < height && b.y > height) || (a.y > height && b.y < height) {
+          crossings.push((self.label_point((i, j), height), (i, j)));
+        }
+      }
+    }
+    convex_hull_2d(crossings)
+  }
+
+  fn label_point(&self, (i, j): (usize, usize), height: f32) -> Vec2 {
+    let (a, b) = (self.points[i], self.points[j]);
+    if a.y == b.y {
+      return a.xz();
+    }
+    a.lerp(b, (height - a.y) / (b.y - a.y)).xz()
+  }
+
+  /// Build the side walls of the water from the bottom up to the local `level`.
+  pub fn build_walls(&self, level: f32) -> Mesh {
+    let mut heights: Vec<f32> = self
+      .points
+      .iter()
+      .map(|p| p.y)
+      .filter(|y| *y > self.bottom && *y < level)
+      .collect();
+    heights.push(self.bottom);
+    heights.push(level.min(self.top));
+    heights.sort_by(f32::total_cmp);
+    heights.dedup();
+
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    let mut indices = Vec::new();
+    for band in heights.windows(2) {
+      let (lo, hi) = (band[0], band[1]);
+      if hi - lo <= f32::EPSILON {
+        continue;
+      }
+      // Between the heights of the hull points the section keeps the same vertices.
+      let ring = self.section((lo + hi) / 2.0, true);
+      for (idx, (_, label_a)) in ring.iter().enumerate() {
+        let (_, label_b) = ring[(idx + 1) % ring.len()];
+        let (a_lo, b_lo) = (
+          self.label_point(*label_a, lo),
+          self.label_point(label_b, lo),
+        );
+        let (a_hi, b_hi) = (
+          self.label_point(*label_a, hi),
+          self.label_point(label_b, hi),
+        );
+        let quad = [
+          Vec3::new(a_lo.x, lo, a_lo.y),
+          Vec3::new(b_lo.x, lo, b_lo.y),
+          Vec3::new(b_hi.x, hi, b_hi.y),
+          Vec3::new(a_hi.x, hi, a_hi.y),
+        ];
+        let normal = (quad[3] - quad[0])
+          .cross(quad[2] - quad[0])
+          .normalize_or_zero();
+        let base = positions.len() as u32;
+        positions.extend(quad.map(|p| p.to_array()));
+        normals.extend([normal.to_array(); 4]);
+        uvs.extend([[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]]);
+        indices.extend_from_slice(&[base, base + 2, base + 1, base, base + 3, base + 2]);
+      }
+    }
+
+    Mesh::new(
+      PrimitiveTopology::TriangleList,
+      RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(indices))
+  }
+
+  /// The walls mesh level and the walls transform for the local water `level`.
+  ///
+  /// The walls of prisms are built up to the top and scaled down to the level.
+  fn walls_level(&self, level: f32) -> (f32, Transform) {
+    let level = level.clamp(self.bottom, self.top);
+    let height = self.top - self.bottom;
+    if !self.is_prism() || height <= 0.0 {
+      return (level, Transform::IDENTITY);
+    }
+    let scale = ((level - self.bottom) / height).max(0.001);
+    let transform = Transform::from_xyz(0.0, self.bottom * (1.0 - scale), 0.0)
+      .with_scale(Vec3::new(1.0, scale, 1.0));
+    (self.top, transform)
+  }
+
+  fn walls_visibility(&self, level: f32) -> Visibility {
+    if self.side_walls && level > self.bottom {
+      Visibility::Inherited
+    } else {
+      Visibility::Hidden
+    }
+  }
+
+  /// Build the water surface at the local `level`.  The mesh is at height 0.0.
+  pub fn build_surface(&self, level: f32) -> Mesh {
+    let section = self.cross_section(level.clamp(self.bottom, self.top));
+    let builder = LakeBuilder::new(section).resolution(self.resolution);
+    builder
+      .build_mesh(&builder.build_lake())
+      .unwrap_or_else(|| {
+        // Keep the vertex attributes, so the mesh still matches the water pipeline.
+        Mesh::new(
+          PrimitiveTopology::TriangleList,
+          RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, Vec::<[f32; 3]>::new())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, Vec::<[f32; 3]>::new())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, Vec::<[f32; 2]>::new())
+      })
+  }
+}
+
+/// The water surface of a `WaterVolume`.  The local height of the transform is the current water level.
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[reflect(Component)]
+pub struct WaterVolumeSurface {
+  #[entities]
+  pub volume: Entity,
+}
+
+/// The side walls of a `WaterVolume`.
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[reflect(Component)]
+pub struct WaterVolumeWalls {
+  #[entities]
+  pub volume: Entity,
+}
+
+fn wall_material(settings: &WaterSettings) -> StandardMaterial {
+  StandardMaterial {
+    base_color: settings.deep_color.with_alpha(settings.base_color.alpha()),
+    alpha_mode: AlphaMode::Blend,
+    perceptual_roughness: 0.22,
+    ..default()
+  }
+}
+
+/// Spawn the surface and side walls of new water volumes.
+pub fn setup_water_volumes(
+  mut commands: Commands,
+  settings: Res<WaterSettings>,
+  mut meshes: ResMut<Assets<Mesh>>,
+  mut water_materials: ResMut<Assets<StandardWaterMaterial>>,
+  mut wall_materials: ResMut<Assets<StandardMaterial>>,
+  volumes: Query<(Entity, &WaterVolume), Added<WaterVolume>>,
+) {
+  for (entity, volume) in volumes.iter() {
+    let level = volume.level(volume.fill);
+    let (walls_level, walls_transform) = volume.walls_level(level);
+    let mut material = settings.new_material(Vec2::ZERO, Vec2::ONE);
+    material.extension.amplitude = volume.amplitude;
+
+    commands.entity(entity).with_children(|parent| {
+      parent.spawn((
+        Name::new("Water Volume Surface"),
+        WaterVolumeSurface { volume: entity },
+        Mesh3d(meshes.add(volume.build_surface(level))),
+        MeshMaterial3d(water_materials.add(material)),
+        WaveDirection::with_duration(
+          settings.wave_direction,
+          settings.wave_direction_blend_duration,
+        ),
+        Transform::from_xyz(0.0, level, 0.0),
+        NotShadowCaster,
+      ));
+      parent.spawn((
+        Name::new("Water Volume Walls"),
+        WaterVolumeWalls { volume: entity },
+        Mesh3d(meshes.add(volume.build_walls(walls_level))),
+        MeshMaterial3d(wall_materials.add(wall_material(&settings))),
+        walls_transform,
+        volume.walls_visibility(level),
+        NotShadowCaster,
+      ));
+    });
+  }
+}
+
+/// Move the water level of volumes when `fill` changes.
+pub fn update_water_volume_level(
+  mut commands: Commands,
+  #[cfg(feature = "easings")] easing_settings: Res<WaterHeightEasingSettings>,
+  volumes: Query<&WaterVolume, Changed<WaterVolume>>,
+  surfaces: Query<(Entity, &WaterVolumeSurface, &Transform)>,
+) {
+  for (entity, surface, transform) in surfaces.iter() {
+    let Ok(volume) = volumes.get(surface.volume) else {
+      continue;
+    };
+    let level = volume.level(volume.fill);
+    if transform.translation.y == level {
+      continue;
+    }
+    let target_transform = Transform::from_xyz(0.0, level, 0.0);
+    #[cfg(feature = "easings")]
+    commands.entity(entity).insert(transform.ease_to(
+      target_transform,
+      easing_settings.height_easing_method,
+      easing_settings.height_easing_type,
+    ));
+    #[cfg(not(feature = "easings"))]
+    commands.entity(entity).insert(target_transform);
+  }
+}
+
+/// The hull points, resolution and surface cross-section a water volume's meshes were built from.
+type WaterVolumeMeshKey = (Vec<Vec3>, f32, Vec<Vec2>);
+
+type WaterVolumeSurfaceQuery = (
+  Entity,
+  &'static WaterVolumeSurface,
+  Ref<'static, Transform>,
+  &'static Mesh3d,
+  &'static MeshMaterial3d<StandardWaterMaterial>,
+);
+
+type WaterVolumeWallsQuery = (
+  &'static WaterVolumeWalls,
+  &'static Mesh3d,
+  &'static MeshMaterial3d<StandardMaterial>,
+  &'static mut Transform,
+  &'static mut Visibility,
+);
+
+/// Update the surface and walls of water volumes when the water level moves.
+///
+/// The meshes are only rebuilt when the hull or the cross-section at the water level changes.
+#[allow(clippy::too_many_arguments)]
+pub fn update_water_volume_meshes(
+  settings: Res<WaterSettings>,
+  mut built: Local<HashMap<Entity, WaterVolumeMeshKey>>,
+  mut meshes: ResMut<Assets<Mesh>>,
+  mut water_materials: ResMut<Assets<StandardWaterMaterial>>,
+  mut wall_materials: ResMut<Assets<StandardMaterial>>,
+  volumes: Query<(Ref<WaterVolume>, &GlobalTransform)>,
+  surfaces: Query<WaterVolumeSurfaceQuery>,
+  mut walls: Query<WaterVolumeWallsQuery, Without<WaterVolumeSurface>>,
+) {
+  built.retain(|entity, _| surfaces.contains(*entity));
+  for (entity, surface, transform, mesh, material) in surfaces.iter() {
+    let Ok((volume, volume_transform)) = volumes.get(surface.volume) else {
+      continue;
+    };
+    let moved = transform.is_changed() || volume.is_changed();
+    let level = transform.translation.y;
+    let section = volume.cross_section(level.clamp(volume.bottom, volume.top));
+    let (hull_changed, section_changed) = match built.get(&entity) {
+      Some((points, resolution, built_section)) => {
+        let hull_changed = *points != volume.points || *resolution != volume.resolution;
+        (
+          hull_changed,
+          hull_changed || !same_section(built_section, &section),
+        )
+      }
+      None => (true, true),
+    };
+
+    if section_changed {
+      if let Some(mesh) = meshes.get_mut(&mesh.0) {
+        *mesh = volume.build_surface(level);
+      }
+    }
+    if section_changed || volume.is_changed() {
+      let bounds = section
+        .iter()
+        .fold(Rect::EMPTY, |rect, p| rect.union_point(*p));
+      let coord_offset = volume_transform.translation().xz() + bounds.min;
+      let coord_scale = bounds.size();
+      let applied = water_materials.get(&material.0).is_none_or(|mat| {
+        mat.extension.amplitude == volume.amplitude
+          && mat.extension.coord_offset == coord_offset
+          && mat.extension.coord_scale == coord_scale
+      });
+      if !applied {
+        if let Some(mat) = water_materials.get_mut(&material.0) {
+          mat.extension.amplitude = volume.amplitude;
+          mat.extension.coord_offset = coord_offset;
+          mat.extension.coord_scale = coord_scale;
+        }
+      }
+    }
+    if section_changed {
+      built.insert(
+        entity,
+        (volume.points.clone(), volume.resolution, section.clone()),
+      );
+    }
+
+    for (walls, mesh, material, mut walls_transform, mut visibility) in walls.iter_mut() {
+      if walls.volume != surface.volume {
+        continue;
+      }
+      if moved {
+        let (walls_level, new_transform) = volume.walls_level(level);
+        // The walls of prisms are scaled to the level, other hulls rebuild them.
+        if hull_changed || new_transform == Transform::IDENTITY {
+          if let Some(mesh) = meshes.get_mut(&mesh.0) {
+            *mesh = volume.build_walls(walls_level);
+          }
+        }
+        walls_transform.set_if_neq(new_transform);
+        visibility.set_if_neq(volume.walls_visibility(level));
+      }
+      if settings.is_changed() {
+        if let Some(mat) = wall_materials.get_mut(&material.0) {
+          *mat = wall_material(&settings);
+        }
+      }
+    }
+  }
+}
+
+/// Returns true if the cross-sections `a` and `b` have the same vertices.
+fn same_section(a: &[Vec2], b: &[Vec2]) -> bool {
+  a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.abs_diff_eq(*b, 1e-5))
+}
+
+/// Andrew's monotone chain, returns a counter-clockwise hull without collinear points.
+fn convex_hull_2d<T: Copy>(mut points: Vec<(Vec2, T)>) -> Vec<(Vec2, T)> {
+  points.sort_by(|a, b| a.0.x.total_cmp(&b.0.x).then(a.0.y.total_cmp(&b.0.y)));
+  points.dedup_by(|a, b| a.0.distance_squared(b.0) < 1e-10);
+  if points.len() < 3 {
+    return points;
+  }
+  let cross = |o: Vec2, a: Vec2, b: Vec2| (a - o).perp_dot(b - o);
+  let mut hull: Vec<(Vec2, T)> = Vec::with_capacity(points.len() * 2);
+  for pass in 0..2 {
+    let start = hull.len();
+    let iter: Box<dyn Iterator<Item = &(Vec2, T)>> = if pass == 0 {
+      Box::new(points.iter())
+    } else {
+      Box::new(points.iter().rev())
+    };
+    for p in iter {
+      while hull.len() >= start + 2
+        && cross(hull[hull.len() - 2].0, hull[hull.len() - 1].0, p.0) <= 0.0
+      {
+        hull.pop();
+      }
+      hull.push(*p);
+    }
+    // The last point is the first point of the next chain.
+    hull.pop();
+  }
+  hull
+}
+
+/// Point in a counter-clockwise convex polygon.
+fn convex_contains(polygon: &[Vec2], p: Vec2) -> bool {
+  polygon.len() >= 3
+    && polygon
+      .iter()
+      .zip(polygon.iter().cycle().skip(1))
+      .all(|(a, b)| (*b - *a).perp_dot(p - *a) >= 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// A square pyramid with its apex at the top, 2 wide at the bottom.
+  fn pyramid() -> WaterVolume {
+    WaterVolume::convex_hull([
+      Vec3::new(-1.0, 0.0, -1.0),
+      Vec3::new(1.0, 0.0, -1.0),
+      Vec3::new(1.0, 0.0, 1.0),
+      Vec3::new(-1.0, 0.0, 1.0),
+      Vec3::new(0.0, 2.0, 0.0),
+    ])
+  }
+
+  fn area(polygon: &[Vec2]) -> f32 {
+    polygon
+      .iter()
+      .zip(polygon.iter().cycle().skip(1))
+      .map(|(a, b)| a.perp_dot(*b))
+      .sum::<f32>()
+      / 2.0
+  }
+
+  #[test]
+  fn cross_section() {
+    let cuboid = WaterVolume::cuboid(Vec3::new(2.0, 1.0, 3.0));
+    for height in [-1.0, 0.0, 0.5, 1.0] {
+      let section = cuboid.cross_section(height);
+      assert_eq!(section.len(), 4, "height {height}");
+      // Counter-clockwise.
+      assert!((area(&section) - 24.0).abs() < 1e-4, "height {height}");
+    }
+    assert!(cuboid.cross_section(1.5).is_empty());
+    assert!(cuboid.is_prism());
+
+    let pyramid = pyramid();
+    assert!(!pyramid.is_prism());
+    let section = pyramid.cross_section(1.0);
+    assert_eq!(section.len(), 4);
+    assert!((area(&section) - 1.0).abs() < 1e-4);
+    assert!(section
+      .iter()
+      .all(|p| p.abs().abs_diff_eq(Vec2::splat(0.5), 1e-5)));
+  }
+
+  #[test]
+  fn contains() {
+    let cuboid = WaterVolume::cuboid(Vec3::new(2.0, 1.0, 3.0));
+    assert!(cuboid.contains(Vec3::ZERO));
+    assert!(cuboid.contains(Vec3::new(2.0, 1.0, 3.0)));
+    assert!(!cuboid.contains(Vec3::new(2.1, 0.0, 0.0)));
+    assert!(!cuboid.contains(Vec3::new(0.0, -1.1, 0.0)));
+
+    let pyramid = pyramid();
+    assert!(pyramid.contains(Vec3::new(0.4, 1.0, 0.4)));
+    assert!(!pyramid.contains(Vec3::new(0.6, 1.0, 0.0)));
+    assert!(pyramid.contains(Vec3::new(0.9, 0.0, -0.9)));
+    assert!(!pyramid.contains(Vec3::new(0.0, 2.1, 0.0)));
+  }
+
+  #[test]
+  fn moving_the_level_keeps_prism_meshes() {
+    let mut app = App::new();
+    app
+      .init_resource::<WaterSettings>()
+      .init_resource::<Assets<Mesh>>()
+      .init_resource::<Assets<StandardWaterMaterial>>()
+      .init_resource::<Assets<StandardMaterial>>()
+      .add_systems(
+        Update,
+        (setup_water_volumes, update_water_volume_meshes).chain(),
+      );
+    let cuboid = app
+      .world_mut()
+      .spawn(WaterVolume::cuboid(Vec3::ONE).with_fill(0.5))
+      .id();
+    let pyramid = app.world_mut().spawn(pyramid().with_fill(0.5)).id();
+    app.update();
+
+    // Replace the meshes with markers, rebuilt meshes have positions.
+    let world = app.world_mut();
+    let handles: Vec<(Entity, Handle<Mesh>)> = world
+      .query::<(&ChildOf, &Mesh3d)>()
+      .iter(world)
+      .map(|(child_of, mesh)| (child_of.parent(), mesh.0.clone()))
+      .collect();
+    let mut meshes = world.resource_mut::<Assets<Mesh>>();
+    for (_, handle) in &handles {
+      let _ = meshes.insert(
+        handle,
+        Mesh::new(
+          PrimitiveTopology::TriangleList,
+          RenderAssetUsages::default(),
+        ),
+      );
+    }
+    for mut transform in world
+      .query_filtered::<&mut Transform, With<WaterVolumeSurface>>()
+      .iter_mut(world)
+    {
+      transform.translation.y += 0.25;
+    }
+    app.update();
+
+    let world = app.world_mut();
+    let meshes = world.resource::<Assets<Mesh>>();
+    for (parent, handle) in &handles {
+      let rebuilt = meshes
+        .get(handle)
+        .unwrap()
+        .attribute(Mesh::ATTRIBUTE_POSITION)
+        .is_some();
+      assert_eq!(rebuilt, *parent == pyramid);
+    }
+    let (_, transform) = world
+      .query::<(&WaterVolumeWalls, &Transform)>()
+      .iter(world)
+      .find(|(walls, _)| walls.volume == cuboid)
+      .unwrap();
+    // The walls go from the bottom to the level at 0.25.
+    assert!((transform.scale.y - 0.625).abs() < 1e-5);
+    assert!((transform.transform_point(Vec3::Y).y - 0.25).abs() < 1e-5);
+  }
+}
@@ -0,0 +1,273 @@
+use bevy::{math::Vec3Swizzles, prelude::*, render::render_resource::ShaderType};
+
+use crate::{material::StandardWaterMaterial, smoothstep};
+
+/// Maximum number of wakes rendered by the water shader.
+pub const MAX_WATER_WAKES: usize = 8;
+
+const GRAVITY: f32 = 9.81;
+/// Half angle of the Kelvin wake wedge (19.47 degrees).
+const KELVIN_TAN: f32 = 0.3536;
+
+/// Kelvin wake behind a moving entity (boats, ships).
+///
+/// The velocity is measured from the entity's `GlobalTransform`.  The wake (V-shaped
+/// transverse and divergent waves plus a foam trail) is added to the water surface
+/// in the water shader and `WaterParam`, so other boats bob over it.
+///
+/// Wakes are only added to world-coordinate materials (water tiles and lakes, see
+/// `WaterMaterial::is_world_aligned`).
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[reflect(Component, Default)]
+#[require(Transform)]
+pub struct Wake {
+  /// Length of the hull.
+  pub hull_length: f32,
+  /// Width of the hull.
+  pub hull_width: f32,
+  /// Height of the wake waves at full speed.
+  pub strength: f32,
+  /// Amount of foam in the trail, 0.0 = none.
+  pub foam: f32,
+  velocity: Vec2,
+  last_position: Option<Vec2>,
+}
+
+impl Default for Wake {
+  fn default() -> Self {
+    Self::new(8.0, 3.0)
+  }
+}
+
+impl Wake {
+  /// Create a wake for a hull with the given size.
+  pub fn new(hull_length: f32, hull_width: f32) -> Self {
+    Self {
+      hull_length,
+      hull_width,
+      strength: 0.3,
+      foam: 0.8,
+      velocity: Vec2::ZERO,
+      last_position: None,
+    }
+  }
+
+  /// Set the height of the wake waves.
+  pub fn with_strength(mut self, strength: f32) -> Self {
+    self.strength = strength;
+    self
+  }
+
+  /// Set the amount of foam in the trail.
+  pub fn with_foam(mut self, foam: f32) -> Self {
+    self.foam = foam;
+    self
+  }
+
+  /// Smoothed velocity of the entity in the X/Z plane.
+  pub fn velocity(&self) -> Vec2 {
+    self.velocity
+  }
+}
+
+/// A wake as uploaded to the water shader.
+#[derive(Clone, Copy, Debug, Default, PartialEq, ShaderType, Reflect)]
+#[reflect(Default, Debug)]
+pub struct WakeData {
+  /// Position of the hull center in wave coordinates.
+  pub position: Vec2,
+  /// Velocity of the hull.
+  pub velocity: Vec2,
+  pub hull_length: f32,
+  pub hull_width: f32,
+  pub strength: f32,
+  pub foam: f32,
+}
+
+impl WakeData {
+  /// Wake height (`x`) and foam (`y`) at the wave coordinate `p`.
+  ///
+  /// Matches `get_wake` in the water shader.
+  pub fn sample(&self, p: Vec2) -> Vec2 {
+    let speed = self.velocity.length();
+    let length = self.hull_length.max(0.1);
+    if speed < 0.1 {
+      return Vec2::ZERO;
+    }
+    let forward = self.velocity / speed;
+    let d = p - self.position;
+    // Distance behind the bow and distance from the path.
+    let x = length * 0.5 - d.dot(forward);
+    let y = d.dot(forward.perp()).abs();
+    if x <= 0.0 {
+      return Vec2::ZERO;
+    }
+
+    // The wake grows with the Froude number.
+    let froude = speed / (GRAVITY * length).sqrt();
+    let amount = (froude * 2.5).clamp(0.0, 1.0);
+    let ramp = smoothstep(0.0, length * 0.5, x);
+    let decay = (1.0 + x / length).sqrt().recip() * (1.0 - smoothstep(6.0, 10.0, x / length));
+
+    // Wave number of the waves travelling with the hull.
+    let k = (GRAVITY / (speed * speed)).min(20.0);
+    let r = y / (x * KELVIN_TAN + self.hull_width * 0.5);
+    let wedge = 1.0 - smoothstep(0.8, 1.1, r);
+    // Transverse waves: crests across the path, curving back towards the wedge edges.
+    let transverse = (k * x * (1.0 + 0.5 * r * r)).cos() * (1.0 - r * r).max(0.0) * wedge;
+    // Divergent waves: short crests along the edges of the wedge.
+    let edge = (-8.0 * (r - 1.0) * (r - 1.0)).exp();
+    let divergent = (1.5 * k * (x * 0.8165 + y * 0.5774)).cos() * edge;
+    let height = self.strength * amount * ramp * decay * (0.5 * transverse + divergent);
+
+    // Foam trail behind the hull.
+    let trail = self.hull_width * 0.5 * (1.0 + 0.15 * x / length);
+    let foam =
+      self.foam * amount * (-(y / trail) * (y / trail)).exp() * (-x / (length * 4.0)).exp();
+
+    Vec2::new(height, foam.min(1.0))
+  }
+}
+
+/// The wakes of all `Wake` entities, uploaded to the water materials.
+#[derive(Resource, Clone, Debug, Default, PartialEq, Reflect)]
+#[reflect(Resource, Default)]
+pub struct WaterWakes {
+  wakes: Vec<WakeData>,
+  /// The `Wake` entity of each wake.
+  #[reflect(ignore)]
+  entities: Vec<Entity>,
+}
+
+impl WaterWakes {
+  /// The active wakes (the fastest `MAX_WATER_WAKES`).
+  pub fn wakes(&self) -> &[WakeData] {
+    &self.wakes
+  }
+
+  /// Combined wake height at the wave coordinate `p`.
+  pub fn height_at(&self, p: Vec2) -> f32 {
+    self.wakes.iter().map(|wake| wake.sample(p).x).sum()
+  }
+
+  /// Combined wake height at the wave coordinate `p`, without the wake of `entity`.
+  ///
+  /// Boats ride the wakes of the other boats, not their own.
+  pub fn height_at_excluding(&self, p: Vec2, entity: Entity) -> f32 {
+    self
+      .wakes
+      .iter()
+      .zip(&self.entities)
+      .filter(|(_, wake_entity)| **wake_entity != entity)
+      .map(|(wake, _)| wake.sample(p).x)
+      .sum()
+  }
+
+  /// Combined wake foam (0.0 to 1.0) at the wave coordinate `p`.
+  pub fn foam_at(&self, p: Vec2) -> f32 {
+    self
+      .wakes
+      .iter()
+      .map(|wake| wake.sample(p).y)
+      .sum::<f32>()
+      .min(1.0)
+  }
+}
+
+/// Measure the velocity of `Wake` entities and collect their wakes.
+pub fn update_wakes(
+  time: Res<Time>,
+  mut wakes: Query<(Entity, &mut Wake, &GlobalTransform)>,
+  mut water_wakes: ResMut<WaterWakes>,
+) {
+  let dt = time.delta_secs();
+  let mut active = Vec::new();
+  for (entity, mut wake, transform) in wakes.iter_mut() {
+    let position = transform.translation().xz();
+    if let Some(last) = wake.last_position.replace(position) {
+      if dt > 0.0 {
+        // Smooth out frame time jitter.
+        let blend = 1.0 - (-dt * 4.0).exp();
+        wake.velocity = wake.velocity.lerp((position - last) / dt, blend);
+      }
+    }
+    let data = WakeData {
+      position,
+      velocity: wake.velocity,
+      hull_length: wake.hull_length,
+      hull_width: wake.hull_width,
+      strength: wake.strength,
+      foam: wake.foam,
+    };
+    active.push((entity, data));
+  }
+  active.sort_by(|(_, a), (_, b)| {
+    b.velocity
+      .length_squared()
+      .total_cmp(&a.velocity.length_squared())
+  });
+  active.truncate(MAX_WATER_WAKES);
+  let (entities, wakes) = active.into_iter().unzip();
+  water_wakes.set_if_neq(WaterWakes { wakes, entities });
+}
+
+/// Upload the wakes to the world-aligned water materials.
+pub fn apply_water_wakes(
+  water_wakes: Res<WaterWakes>,
+  mut materials: ResMut<Assets<StandardWaterMaterial>>,
+) {
+  let mut data = [WakeData::default(); MAX_WATER_WAKES];
+  data[..water_wakes.wakes.len()].copy_from_slice(&water_wakes.wakes);
+  let count = water_wakes.wakes.len() as u32;
+  // Rivers and volumes don't use world X/Z wave coordinates.
+  let stale: Vec<_> = materials
+    .iter()
+    .filter(|(_, mat)| mat.extension.is_world_aligned())
+    .filter(|(_, mat)| {
+      mat.extension.wake_count != count
+        || mat.extension.wakes != data
+        || (count > 0 && !mat.extension.wakes_enabled)
+    })
+    .map(|(id, _)| id)
+    .collect();
+  for id in stale {
+    if let Some(mat) = materials.get_mut(id) {
+      mat.extension.wakes = data;
+      mat.extension.wake_count = count;
+      mat.extension.wakes_enabled |= count > 0;
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn boats_ride_other_wakes() {
+    let wake = |position: Vec2| WakeData {
+      position,
+      velocity: Vec2::new(0.0, 6.0),
+      hull_length: 8.0,
+      hull_width: 3.0,
+      strength: 0.3,
+      foam: 0.8,
+    };
+    let (a, b) = (
+      Entity::from_raw_u32(1).unwrap(),
+      Entity::from_raw_u32(2).unwrap(),
+    );
+    let wakes = WaterWakes {
+      wakes: vec![wake(Vec2::ZERO), wake(Vec2::new(2.0, 10.0))],
+      entities: vec![a, b],
+    };
+
+    // The own wake is not zero at the hull center.
+    let p = Vec2::ZERO;
+    let own = wakes.wakes[0].sample(p).x;
+    assert!(own.abs() > 1e-4, "own wake {own}");
+    let others = wakes.height_at_excluding(p, a);
+    assert!((others - wakes.wakes[1].sample(p).x).abs() < 1e-6);
+    assert!((wakes.height_at(p) - own - others).abs() < 1e-6);
+  }
+}
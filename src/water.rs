@@ -3,18 +3,32 @@ use bevy::mesh::*;
 use bevy::prelude::*;
 #[cfg(feature = "easings")]
 pub use bevy_easings::{Ease, EaseFunction, EaseMethod, EasingType, EasingsPlugin};
+use serde::{Deserialize, Serialize};
 
 pub mod material;
 use material::*;
 
-use crate::{mix2d, sample_directional_wave_blended, smoothstep};
+use crate::{
+  apply_water_flow, apply_water_presets, apply_water_sphere, apply_water_wakes, apply_wind,
+  emit_water_ripples, mix2d, reconcile_water, sample_directional_wave_blended, setup_water_volumes,
+  smoothstep, spawn_water_splashes, update_global_tide, update_tides, update_wakes,
+  update_water_blend, update_water_contacts, update_water_ripples, update_water_splashes,
+  update_water_timelines, update_water_volume_level, update_water_volume_meshes, HighTide, Lake,
+  LowTide, RippleEmitter, RippleFocus, River, SplashParticle, Submerged, Tide, Wake, WaterBlend,
+  WaterContact, WaterEntered, WaterExited, WaterFlow, WaterPreset, WaterPresetHandle,
+  WaterPresetLoader, WaterReconcile, WaterRipples, WaterSphere, WaterSplashSettings, WaterTimeline,
+  WaterVolume, WaterVolumeSurface, WaterVolumeWalls, WaterWakes, Wind,
+};
 
 /// Component for tracking wave direction using dual-direction crossfade blending.
 ///
 /// Instead of rotating waves (which looks unnatural), this crossfades between
 /// two independent wave patterns: the old direction fading out while the new
 /// direction fades in. This matches how real water responds to changing wind.
-#[derive(Component, Clone, Copy, Debug)]
+///
+/// The crossfade state is reflected, so it's restored with saved scenes.
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[reflect(Component, Default)]
 pub struct WaveDirection {
   /// Direction A (fading out).
   dir_a: Vec2,
@@ -26,6 +40,9 @@ pub struct WaveDirection {
   blend_duration: f32,
   /// Per-tile offset for desynchronized transitions (0.0-0.3 typical).
   pub tile_offset: f32,
+  /// A crossfade started since the last update (not saved).
+  #[reflect(ignore)]
+  started: bool,
 }
 
 impl Default for WaveDirection {
@@ -44,6 +61,7 @@ impl WaveDirection {
       blend: 1.0, // Start fully blended (no transition)
       blend_duration: 2.0,
       tile_offset: 0.0,
+      started: false,
     }
   }
 
@@ -56,6 +74,7 @@ impl WaveDirection {
       blend: 1.0,
       blend_duration,
       tile_offset: 0.0,
+      started: false,
     }
   }
 
@@ -72,6 +91,15 @@ impl WaveDirection {
     self.dir_a = self.current_blended();
     self.dir_b = normalized;
     self.blend = 0.0;
+    self.started = true;
+  }
+
+  /// Set the crossfade state directly (e.g. from a `WaterSnapshot`).
+  pub fn set_crossfade(&mut self, dir_a: Vec2, dir_b: Vec2, blend: f32) {
+    self.started |= self.dir_b != dir_b;
+    self.dir_a = dir_a;
+    self.dir_b = dir_b;
+    self.blend = blend.clamp(0.0, 1.0);
   }
 
   /// Set the blend duration in seconds.
@@ -126,13 +154,121 @@ impl WaveDirection {
   }
 }
 
+/// Sent when a wave direction crossfade starts.
+///
+/// The water tiles crossfade together and send one message.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct WaveDirectionChanged {
+  /// The water body, `None` for the global water tiles.
+  pub body: Option<Entity>,
+  /// Direction fading out.
+  pub from: Vec2,
+  /// Direction fading in.
+  pub to: Vec2,
+  /// Duration of the crossfade in seconds.
+  pub duration: f32,
+}
+
+/// Sent when a wave direction crossfade is done.
+///
+/// The water tiles crossfade together and send one message.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct WaveDirectionSettled {
+  /// The water body, `None` for the global water tiles.
+  pub body: Option<Entity>,
+  /// The new wave direction.
+  pub direction: Vec2,
+}
+
+/// Sent when `WaterSettings::height` changes (the `Tide` sends `HighTide`/`LowTide`).
+#[derive(Message, Clone, Copy, Debug)]
+pub struct WaterHeightChanged {
+  pub previous: f32,
+  pub height: f32,
+}
+
+/// Sent when `WaterSettings::water_quality` changes.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct WaterQualityChanged {
+  pub previous: WaterQuality,
+  pub quality: WaterQuality,
+}
+
+/// Default size of a water tile (`WaterSettings::tile_size`).
 pub const WATER_SIZE: u32 = 256;
 pub const WATER_HALF_SIZE: f32 = WATER_SIZE as f32 / 2.0;
+/// Default grid size (`WaterSettings::spawn_tiles`).
 pub const WATER_GRID_SIZE: u32 = 6;
+/// Maximum subdivisions of a water tile mesh.
+const MAX_TILE_SUBDIVISIONS: u32 = 1024;
+
+/// Subdivisions of the water tile mesh for each `WaterQuality`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+#[reflect(Default)]
+#[serde(default)]
+pub struct WaterTileSubdivisions {
+  pub basic: u32,
+  pub medium: u32,
+  pub high: u32,
+  pub ultra: u32,
+}
+
+impl Default for WaterTileSubdivisions {
+  fn default() -> Self {
+    Self {
+      basic: 0,
+      medium: 0,
+      high: WATER_SIZE / 16,
+      ultra: WATER_SIZE / 4,
+    }
+  }
+}
+
+impl WaterTileSubdivisions {
+  /// Subdivisions for `quality`.
+  pub fn get(&self, quality: WaterQuality) -> u32 {
+    match quality {
+      WaterQuality::Basic => self.basic,
+      WaterQuality::Medium => self.medium,
+      WaterQuality::High => self.high,
+      WaterQuality::Ultra => self.ultra,
+    }
+  }
+}
+
+/// Invalid `WaterSettings` for the water tiles.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WaterSettingsError {
+  /// The tile size must be positive.
+  InvalidTileSize(f32),
+  /// The grid must have at least one tile.
+  EmptyGrid(UVec2),
+  /// Too many tile mesh subdivisions.
+  TooManySubdivisions(u32),
+}
+
+impl std::fmt::Display for WaterSettingsError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::InvalidTileSize(size) => write!(f, "Invalid water tile size: {size}"),
+      Self::EmptyGrid(grid) => write!(f, "Empty water tile grid: {grid}"),
+      Self::TooManySubdivisions(subdivisions) => write!(
+        f,
+        "Too many water tile subdivisions: {subdivisions} (max {MAX_TILE_SUBDIVISIONS})"
+      ),
+    }
+  }
+}
+
+impl std::error::Error for WaterSettingsError {}
 
 /// Global wave transition state for physics calculations.
 /// This mirrors the shader's wave direction blending state.
-#[derive(Resource, Clone, Copy, Debug)]
+///
+/// Also keeps the wave clock.  When the resource is restored from a saved scene
+/// the waves continue from the saved `wave_time`.
+#[derive(Resource, Clone, Copy, Debug, Reflect)]
+#[reflect(Resource, Default)]
 pub struct GlobalWaveState {
   /// Direction A (fading out).
   pub dir_a: Vec2,
@@ -140,6 +276,14 @@ pub struct GlobalWaveState {
   pub dir_b: Vec2,
   /// Blend factor: 0 = fully A, 1 = fully B.
   pub blend: f32,
+  /// Speed of the wave animation (see `WaterSettings::wave_speed`).
+  pub wave_speed: f32,
+  /// Offset of the wave time, keeps the waves continuous when the speed changes.
+  pub time_offset: f32,
+  /// Wave time at the last update.
+  pub wave_time: f32,
+  /// Bevy `time.elapsed_secs_wrapped()` at the last update.
+  pub clock_time: f32,
 }
 
 impl Default for GlobalWaveState {
@@ -149,6 +293,10 @@ impl Default for GlobalWaveState {
       dir_a: default_dir,
       dir_b: default_dir,
       blend: 1.0,
+      wave_speed: 1.0,
+      time_offset: 0.0,
+      wave_time: 0.0,
+      clock_time: 0.0,
     }
   }
 }
@@ -164,9 +312,34 @@ impl GlobalWaveState {
   pub fn current_direction(&self) -> Vec2 {
     self.dir_a.lerp(self.dir_b, self.blend).normalize_or_zero()
   }
+
+  /// Wave animation time for the Bevy `time.elapsed_secs_wrapped()` (matches the shader).
+  pub fn wave_time(&self, time: f32) -> f32 {
+    time * self.wave_speed + self.time_offset
+  }
+
+  /// Change the wave speed at `time`, without jumping the waves.
+  pub fn set_wave_speed(&mut self, time: f32, wave_speed: f32) {
+    self.time_offset += time * (self.wave_speed - wave_speed);
+    self.wave_speed = wave_speed;
+    self.wave_time = self.wave_time(time);
+    self.clock_time = time;
+  }
+
+  /// Advance the wave clock to `time`.
+  pub fn update_clock(&mut self, time: f32) {
+    self.wave_time = self.wave_time(time);
+    self.clock_time = time;
+  }
+
+  /// Continue the waves from the saved `wave_time` at `time` (after loading a saved game).
+  pub fn restore_clock(&mut self, time: f32) {
+    self.time_offset = self.wave_time - time * self.wave_speed;
+    self.clock_time = time;
+  }
 }
 
-#[derive(Debug, Clone, Copy, Reflect)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect, Serialize, Deserialize)]
 #[repr(u32)]
 pub enum WaterQuality {
   Basic,
@@ -175,6 +348,13 @@ pub enum WaterQuality {
   Ultra,
 }
 
+impl WaterQuality {
+  /// Returns true if the water tiles receive shadows.
+  pub fn receives_shadows(self) -> bool {
+    matches!(self, WaterQuality::High | WaterQuality::Ultra)
+  }
+}
+
 impl Into<u32> for WaterQuality {
   fn into(self) -> u32 {
     match self {
@@ -187,7 +367,7 @@ impl Into<u32> for WaterQuality {
 }
 
 #[derive(Resource, Clone, Debug, Reflect)]
-#[reflect(Resource)]
+#[reflect(Resource, Default)]
 pub struct WaterSettings {
   /// StandardMaterial setting.
   pub alpha_mode: AlphaMode,
@@ -213,7 +393,15 @@ pub struct WaterSettings {
   /// This allows easy editing all materials.
   pub update_materials: bool,
   /// During startup, spawn a 2d grid of water tiles.
+  ///
+  /// Changing the grid at runtime adds/removes tiles.
   pub spawn_tiles: Option<UVec2>,
+  /// Size of a water tile in world units.
+  pub tile_size: f32,
+  /// Subdivisions of the water tile mesh for each `WaterQuality`.
+  ///
+  /// Scale them with the `tile_size` to keep the same vertex density.
+  pub tile_subdivisions: WaterTileSubdivisions,
   /// Water quality
   ///
   pub water_quality: WaterQuality,
@@ -222,6 +410,50 @@ pub struct WaterSettings {
   /// Duration in seconds for wave direction crossfade transitions.
   /// Default: 2.0. Longer = more gradual, imperceptible transitions.
   pub wave_direction_blend_duration: f32,
+  /// Enable screen-space refraction of objects below the surface.
+  ///
+  /// This is an alternative to `AlphaMode::Blend`, the water is rendered as an opaque
+  /// transmissive material (`alpha_mode` is ignored).  Add a `DepthPrepass` to the
+  /// camera for the depth-based color, shore fading and absorption, without it the
+  /// opacity only depends on the `clarity`.
+  pub refraction: bool,
+  /// Strength of the refraction distortion caused by the wave normal.
+  pub refraction_strength: f32,
+  /// Chromatic dispersion of the refraction, 0.0 = none.
+  pub refraction_dispersion: f32,
+  /// Color of sunlight scattered through the wave crests.
+  pub subsurface_color: Color,
+  /// Strength of the subsurface scattering, 0.0 = disabled.
+  pub subsurface_strength: f32,
+  /// Tangent-space normal map for fine ripples layered over the waves.
+  ///
+  /// The one map is sampled as two scrolling layers with different scales and directions.
+  pub detail_normal_map: Option<Handle<Image>>,
+  /// Strength of the detail normals.
+  pub detail_normal_strength: f32,
+  /// World-space scale of the two detail normal layers.
+  pub detail_normal_scale: Vec2,
+  /// Scroll speed of the detail normals along the wave direction.
+  pub detail_normal_speed: f32,
+  /// Distance from the camera where the detail normals have faded out.
+  pub detail_fade_distance: f32,
+  /// Flow of the water (currents).  Advects the wave pattern and detail normals.
+  pub flow: WaterFlow,
+  /// Scale of the wave length, 1.0 = default.
+  pub wave_length: f32,
+  /// Speed of the wave animation, 1.0 = default.
+  ///
+  /// Speed changes are smooth, the wave time is offset to keep the waves continuous.
+  pub wave_speed: f32,
+  /// Rain intensity, 0.0 = no rain, 1.0 = a raindrop in every ripple cell.
+  ///
+  /// Adds small ring ripples from raindrops to the water normals.  The rain shader variant
+  /// is compiled the first time it rains, later changes only update the uniform.
+  pub rain_intensity: f32,
+  /// World size of a raindrop ripple.
+  pub rain_ripple_size: f32,
+  /// Strength of the raindrop ripple normals.
+  pub rain_ripple_strength: f32,
 }
 
 impl Default for WaterSettings {
@@ -241,9 +473,142 @@ impl Default for WaterSettings {
       edge_color: Color::srgba(1.0, 1.0, 1.0, 1.0),
       update_materials: true,
       spawn_tiles: Some(UVec2::new(WATER_GRID_SIZE, WATER_GRID_SIZE)),
+      tile_size: WATER_SIZE as f32,
+      tile_subdivisions: WaterTileSubdivisions::default(),
       water_quality: WaterQuality::Ultra,
       wave_direction: Vec2::new(1.0, 2.0),
       wave_direction_blend_duration: 2.0,
+      refraction: false,
+      refraction_strength: 0.05,
+      refraction_dispersion: 0.1,
+      subsurface_color: Color::srgba(0.1, 0.75, 0.65, 1.0),
+      subsurface_strength: 0.5,
+      detail_normal_map: None,
+      detail_normal_strength: 0.5,
+      detail_normal_scale: Vec2::new(0.25, 0.1),
+      detail_normal_speed: 0.5,
+      detail_fade_distance: 100.0,
+      flow: WaterFlow::default(),
+      wave_length: 1.0,
+      wave_speed: 1.0,
+      rain_intensity: 0.0,
+      rain_ripple_size: 0.4,
+      rain_ripple_strength: 0.4,
+    }
+  }
+}
+
+impl WaterSettings {
+  /// Check the water tile settings, including the subdivisions of every quality level.
+  pub fn validate(&self) -> Result<(), WaterSettingsError> {
+    if !(self.tile_size.is_finite() && self.tile_size > 0.0) {
+      return Err(WaterSettingsError::InvalidTileSize(self.tile_size));
+    }
+    if let Some(grid) = self.spawn_tiles {
+      if grid.x == 0 || grid.y == 0 {
+        return Err(WaterSettingsError::EmptyGrid(grid));
+      }
+    }
+    // All quality levels, the quality can change at runtime.
+    let subdivisions = &self.tile_subdivisions;
+    let qualities = [
+      WaterQuality::Basic,
+      WaterQuality::Medium,
+      WaterQuality::High,
+      WaterQuality::Ultra,
+    ];
+    if let Some(subdivisions) = qualities
+      .map(|quality| subdivisions.get(quality))
+      .into_iter()
+      .find(|subdivisions| *subdivisions > MAX_TILE_SUBDIVISIONS)
+    {
+      return Err(WaterSettingsError::TooManySubdivisions(subdivisions));
+    }
+    Ok(())
+  }
+
+  /// Create a new water material from these settings.
+  ///
+  /// `coord_offset` and `coord_scale` map the mesh UVs to world-aligned wave coordinates.
+  pub fn new_material(&self, coord_offset: Vec2, coord_scale: Vec2) -> StandardWaterMaterial {
+    let normalized_dir = self.wave_direction.normalize_or_zero();
+    let mut base = StandardMaterial {
+      #[cfg(not(feature = "ssr"))]
+      perceptual_roughness: 0.22,
+      #[cfg(feature = "ssr")]
+      perceptual_roughness: 0.0,
+      ..default()
+    };
+    self.apply_base(&mut base);
+    let mut extension = WaterMaterial {
+      coord_offset,
+      coord_scale,
+      wave_dir_a: normalized_dir,
+      wave_dir_b: normalized_dir,
+      wave_blend: 1.0,
+      ..default()
+    };
+    self.apply_extension(&mut extension);
+    StandardWaterMaterial { base, extension }
+  }
+
+  /// Create a water material with world X/Z wave coordinates (water tiles and lakes).
+  pub fn new_grid_material(&self) -> StandardWaterMaterial {
+    let mut material = self.new_material(Vec2::ZERO, Vec2::ONE);
+    material.extension.world_coords = true;
+    material
+  }
+
+  /// Update the `WaterMaterial` fields of a water material from these settings.
+  pub fn apply_extension(&self, extension: &mut WaterMaterial) {
+    extension.amplitude = self.amplitude;
+    extension.clarity = self.clarity;
+    extension.deep_color = self.deep_color;
+    extension.shallow_color = self.shallow_color;
+    extension.edge_color = self.edge_color;
+    extension.edge_scale = self.edge_scale;
+    extension.quality = self.water_quality.into();
+    extension.refraction = self.refraction;
+    extension.refraction_strength = self.refraction_strength;
+    extension.refraction_dispersion = self.refraction_dispersion;
+    extension.subsurface_color = self.subsurface_color;
+    extension.subsurface_strength = self.subsurface_strength;
+    extension.detail_normal_map = self.detail_normal_map.clone();
+    extension.detail_normal_strength = self.detail_normal_strength;
+    extension.detail_normal_scale = self.detail_normal_scale;
+    extension.detail_normal_speed = self.detail_normal_speed;
+    extension.detail_fade_distance = self.detail_fade_distance;
+    extension.wave_length = self.wave_length;
+    extension.rain_intensity = self.rain_intensity;
+    extension.rain_enabled |= self.rain_intensity > 0.0;
+    extension.rain_ripple_size = self.rain_ripple_size;
+    extension.rain_ripple_strength = self.rain_ripple_strength;
+    self.flow.apply_to(extension);
+  }
+
+  /// Returns `true` if `material` already matches these settings.
+  pub fn is_applied(&self, material: &StandardWaterMaterial) -> bool {
+    let mut base = material.base.clone();
+    self.apply_base(&mut base);
+    let mut extension = material.extension.clone();
+    self.apply_extension(&mut extension);
+    base.base_color == material.base.base_color
+      && base.alpha_mode == material.base.alpha_mode
+      && base.specular_transmission == material.base.specular_transmission
+      && extension == material.extension
+  }
+
+  /// Update the `StandardMaterial` fields of a water material from these settings.
+  pub fn apply_base(&self, base: &mut StandardMaterial) {
+    base.base_color = self.base_color;
+    if self.refraction {
+      // The water must be opaque and transmissive for Bevy to copy the
+      // opaque scene into the view transmission texture.
+      base.alpha_mode = AlphaMode::Opaque;
+      base.specular_transmission = 1.0;
+    } else {
+      base.alpha_mode = self.alpha_mode;
+      base.specular_transmission = 0.0;
     }
   }
 }
@@ -272,11 +637,13 @@ impl Default for WaterHeightEasingSettings {
   }
 }
 
-#[derive(Component, Default)]
+#[derive(Component, Default, Reflect)]
+#[reflect(Component, Default)]
 #[require(Transform, Visibility)]
 pub struct WaterTiles;
 
-#[derive(Component, Default)]
+#[derive(Component, Default, Reflect)]
+#[reflect(Component, Default)]
 #[require(Mesh3d, MeshMaterial3d<StandardWaterMaterial>, Transform, Visibility)]
 pub struct WaterTile {
   pub offset: Vec2,
@@ -284,10 +651,15 @@ pub struct WaterTile {
 
 impl WaterTile {
   pub fn new(height: f32, offset: Vec2) -> (Self, Name, Transform) {
+    Self::with_size(height, offset, WATER_SIZE as f32)
+  }
+
+  /// A tile of `tile_size` with its corner at `offset`.
+  pub fn with_size(height: f32, offset: Vec2, tile_size: f32) -> (Self, Name, Transform) {
     // The tile position is based on the center of the tile,
-    // so we need to add `WATER_HALF_SIZE` so the tile corner absolute position
+    // so we need to add half the tile size so the tile corner absolute position
     // will match `coord` in the water shader.
-    let tile_pos = offset + WATER_HALF_SIZE;
+    let tile_pos = offset + tile_size / 2.0;
     (
       WaterTile { offset },
       Name::new(format!("Water Tile {}x{}", offset.x, offset.y)),
@@ -296,98 +668,266 @@ impl WaterTile {
   }
 }
 
+/// Mesh of a water tile for the `tile_size`, `tile_subdivisions` and `water_quality` settings.
+pub fn water_tile_mesh(settings: &WaterSettings) -> Mesh {
+  PlaneMeshBuilder::from_length(settings.tile_size)
+    .subdivisions(settings.tile_subdivisions.get(settings.water_quality))
+    .build()
+}
+
+/// Wave coordinates of the tile corners of a centered grid of water tiles.
+pub fn water_grid_offsets(grid: UVec2, tile_size: f32) -> impl Iterator<Item = Vec2> {
+  let grid_center = grid.as_vec2() * tile_size / 2.0;
+  (0..grid.x).flat_map(move |x| {
+    (0..grid.y).map(move |y| UVec2::new(x, y).as_vec2() * tile_size - grid_center)
+  })
+}
+
+/// Spawn a water tile at `height` with its corner at `coord_offset` as a child of `parent`.
+///
+/// All tiles share the `mesh` and the `material` (see `WaterSettings::new_grid_material`).
+#[allow(clippy::too_many_arguments)]
+fn spawn_water_tile(
+  commands: &mut Commands,
+  parent: Entity,
+  settings: &WaterSettings,
+  height: f32,
+  wave_state: &GlobalWaveState,
+  mesh: &Mesh3d,
+  material: &MeshMaterial3d<StandardWaterMaterial>,
+  coord_offset: Vec2,
+) {
+  let (x, y) = (coord_offset.x, coord_offset.y);
+  // Per-tile offset for desynchronized transitions (based on tile position)
+  let tile_hash = ((x as i32).wrapping_mul(73856093) ^ (y as i32).wrapping_mul(19349663)) as f32;
+  let tile_offset = (tile_hash.abs() % 1000.0) / 1000.0 * 0.3; // 0-0.3 range
+
+  let mut wave_dir = WaveDirection::with_duration(
+    settings.wave_direction,
+    settings.wave_direction_blend_duration,
+  );
+  // Join a running crossfade of the other tiles.
+  if wave_state.blend < 1.0 {
+    wave_dir.set_crossfade(wave_state.dir_a, wave_state.dir_b, wave_state.blend);
+    wave_dir.started = false;
+  }
+  wave_dir.tile_offset = tile_offset;
+
+  let mut tile_bundle = commands.spawn((
+    WaterTile::with_size(height, coord_offset, settings.tile_size),
+    mesh.clone(),
+    material.clone(),
+    wave_dir,
+    NotShadowCaster,
+    ChildOf(parent),
+  ));
+
+  if !settings.water_quality.receives_shadows() {
+    tile_bundle.insert(NotShadowReceiver);
+  }
+}
+
 /// Setup water.
 pub fn setup_water(
   mut commands: Commands,
   settings: Res<WaterSettings>,
+  wave_state: Res<GlobalWaveState>,
   mut meshes: ResMut<Assets<Mesh>>,
   mut materials: ResMut<Assets<StandardWaterMaterial>>,
-) {
+  tide: Option<Res<Tide>>,
+) -> Result {
+  settings.validate()?;
+  let height = tide.map(|tide| tide.height()).unwrap_or(settings.height);
   let grid = match settings.spawn_tiles {
     Some(grid) => grid,
     None => {
-      return;
+      return Ok(());
     }
   };
-  let water_height = settings.height;
-  let mut plane_builder = PlaneMeshBuilder::from_length(WATER_SIZE as f32);
-  plane_builder = match settings.water_quality {
-    WaterQuality::Basic => plane_builder,
-    WaterQuality::Medium => plane_builder,
-    WaterQuality::High => plane_builder.subdivisions(WATER_SIZE as u32 / 16),
-    WaterQuality::Ultra => plane_builder.subdivisions(WATER_SIZE as u32 / 4),
-  };
 
   // Generate mesh for water.
-  let mesh = Mesh3d(meshes.add(plane_builder));
-
-  commands
-    .spawn((WaterTiles, Name::new("Water")))
-    .with_children(|parent| {
-      let grid_center_x = (WATER_SIZE * grid.x) as f32 / 2.0;
-      let grid_center_y = (WATER_SIZE * grid.y) as f32 / 2.0;
-      for x in 0..grid.x {
-        for y in 0..grid.y {
-          let x = (x * WATER_SIZE) as f32 - grid_center_x;
-          let y = (y * WATER_SIZE) as f32 - grid_center_y;
-          // UV starts at (0,0) at the corner.
-          let coord_offset = Vec2::new(x, y);
-          // Water material.
-          // Per-tile offset for desynchronized transitions (based on tile position)
-          let tile_hash =
-            ((x as i32).wrapping_mul(73856093) ^ (y as i32).wrapping_mul(19349663)) as f32;
-          let tile_offset = (tile_hash.abs() % 1000.0) / 1000.0 * 0.3; // 0-0.3 range
-
-          let normalized_dir = settings.wave_direction.normalize_or_zero();
-          let material = MeshMaterial3d(materials.add(StandardWaterMaterial {
-            base: StandardMaterial {
-              base_color: settings.base_color,
-              alpha_mode: settings.alpha_mode,
-              #[cfg(not(feature = "ssr"))]
-              perceptual_roughness: 0.22,
-              #[cfg(feature = "ssr")]
-              perceptual_roughness: 0.0,
-              ..default()
-            },
-            extension: WaterMaterial {
-              amplitude: settings.amplitude,
-              clarity: settings.clarity,
-              deep_color: settings.deep_color,
-              shallow_color: settings.shallow_color,
-              edge_color: settings.edge_color,
-              edge_scale: settings.edge_scale,
-              coord_offset,
-              coord_scale: Vec2::new(WATER_SIZE as f32, WATER_SIZE as f32),
-              wave_dir_a: normalized_dir,
-              wave_dir_b: normalized_dir,
-              wave_blend: 1.0,
-              quality: settings.water_quality.into(),
-            },
-          }));
-
-          let mut wave_dir = WaveDirection::with_duration(
-            settings.wave_direction,
-            settings.wave_direction_blend_duration,
-          );
-          wave_dir.tile_offset = tile_offset;
-
-          let mut tile_bundle = parent.spawn((
-            WaterTile::new(water_height, coord_offset),
-            mesh.clone(),
-            material,
-            wave_dir,
-            NotShadowCaster,
-          ));
-
-          match settings.water_quality {
-            WaterQuality::Basic | WaterQuality::Medium => {
-              tile_bundle.insert(NotShadowReceiver);
-            }
-            _ => {}
-          };
-        }
+  let mesh = Mesh3d(meshes.add(water_tile_mesh(&settings)));
+  let material = MeshMaterial3d(materials.add(settings.new_grid_material()));
+
+  let parent = commands.spawn((WaterTiles, Name::new("Water"))).id();
+  for coord_offset in water_grid_offsets(grid, settings.tile_size) {
+    spawn_water_tile(
+      &mut commands,
+      parent,
+      &settings,
+      height,
+      &wave_state,
+      &mesh,
+      &material,
+      coord_offset,
+    );
+  }
+  Ok(())
+}
+
+/// Despawn the water tiles.
+pub fn despawn_water_tiles(mut commands: Commands, water: Query<Entity, With<WaterTiles>>) {
+  for entity in water.iter() {
+    commands.entity(entity).despawn();
+  }
+}
+
+type WaterTileQuery = (
+  Entity,
+  &'static WaterTile,
+  &'static Mesh3d,
+  &'static MeshMaterial3d<StandardWaterMaterial>,
+  &'static ChildOf,
+);
+
+/// Settings the water tile grid is built from (`spawn_tiles`, `tile_size`, `tile_subdivisions`, `water_quality`).
+type WaterGridKey = (Option<UVec2>, f32, WaterTileSubdivisions, WaterQuality);
+
+/// Add or remove water tiles when `WaterSettings::spawn_tiles` changes.
+///
+/// Tiles that are still part of the new grid are kept.  Setting `spawn_tiles` to `None`
+/// despawns the water tiles.  Changing the `tile_size` or `tile_subdivisions` rebuilds
+/// all tiles.  Changing the `water_quality` swaps the tile mesh and `NotShadowReceiver`.
+///
+/// Invalid settings (see `WaterSettings::validate`) log a warning and keep the current tiles.
+#[allow(clippy::too_many_arguments)]
+pub fn update_water_grid(
+  mut commands: Commands,
+  settings: Res<WaterSettings>,
+  wave_state: Res<GlobalWaveState>,
+  mut meshes: ResMut<Assets<Mesh>>,
+  mut materials: ResMut<Assets<StandardWaterMaterial>>,
+  water: Query<Entity, With<WaterTiles>>,
+  tiles: Query<WaterTileQuery>,
+  tide: Option<Res<Tide>>,
+  mut last_grid: Local<Option<WaterGridKey>>,
+) {
+  // Only react to changes, the first grid is spawned by `setup_water`.
+  let grid = (
+    settings.spawn_tiles,
+    settings.tile_size,
+    settings.tile_subdivisions,
+    settings.water_quality,
+  );
+  let Some(last) = *last_grid else {
+    *last_grid = Some(grid);
+    return;
+  };
+  if last == grid {
+    return;
+  }
+  // Keep the current grid for invalid runtime changes (e.g. from a graphics menu).
+  if let Err(error) = settings.validate() {
+    warn!("Invalid water settings, keeping the water tiles: {error}");
+    return;
+  }
+  *last_grid = Some(grid);
+  let Some(grid_size) = settings.spawn_tiles else {
+    despawn_water_tiles(commands, water);
+    return;
+  };
+  let height = tide.map(|tide| tide.height()).unwrap_or(settings.height);
+  // A new tile size or mesh, rebuild all tiles.
+  let rebuild = (last.1, last.2) != (grid.1, grid.2);
+  // The tile mesh also depends on the quality.
+  let new_mesh = rebuild || last.3 != grid.3;
+
+  let mesh = tiles
+    .iter()
+    .map(|(_, _, mesh, ..)| mesh.clone())
+    .next()
+    .filter(|_| !new_mesh)
+    .unwrap_or_else(|| Mesh3d(meshes.add(water_tile_mesh(&settings))));
+  let offsets: Vec<_> = water_grid_offsets(grid_size, settings.tile_size).collect();
+  for (entity, tile, ..) in tiles.iter() {
+    if rebuild || !offsets.contains(&tile.offset) {
+      commands.entity(entity).despawn();
+    } else if new_mesh {
+      let mut tile = commands.entity(entity);
+      tile.insert(mesh.clone());
+      if settings.water_quality.receives_shadows() {
+        tile.remove::<NotShadowReceiver>();
+      } else {
+        tile.insert(NotShadowReceiver);
       }
-    });
+    }
+  }
+  let parent = tiles
+    .iter()
+    .map(|(.., child_of)| child_of.parent())
+    .chain(water.iter())
+    .next()
+    .unwrap_or_else(|| commands.spawn((WaterTiles, Name::new("Water"))).id());
+  // The material doesn't depend on the tile size.
+  let material = tiles
+    .iter()
+    .map(|(_, _, _, material, _)| material.clone())
+    .next()
+    .unwrap_or_else(|| MeshMaterial3d(materials.add(settings.new_grid_material())));
+  for coord_offset in offsets {
+    if !rebuild
+      && tiles
+        .iter()
+        .any(|(_, tile, ..)| tile.offset == coord_offset)
+    {
+      continue;
+    }
+    spawn_water_tile(
+      &mut commands,
+      parent,
+      &settings,
+      height,
+      &wave_state,
+      &mesh,
+      &material,
+      coord_offset,
+    );
+  }
+}
+
+/// Commands to despawn or rebuild the water tiles.
+pub trait WaterCommandsExt {
+  /// Despawn the water tiles.
+  fn despawn_water_tiles(&mut self);
+
+  /// Despawn the water tiles and spawn them again from the `WaterSettings`.
+  fn rebuild_water_tiles(&mut self);
+}
+
+impl WaterCommandsExt for Commands<'_, '_> {
+  fn despawn_water_tiles(&mut self) {
+    self.run_system_cached(despawn_water_tiles);
+  }
+
+  fn rebuild_water_tiles(&mut self) {
+    self.run_system_cached(despawn_water_tiles);
+    self.run_system_cached(setup_water);
+  }
+}
+
+/// Update the shader specialization of the tile materials when the `WaterSettings::water_quality`
+/// changes (also when `update_materials` is disabled).
+///
+/// The tile mesh and the `NotShadowReceiver` component are swapped by `update_water_grid`.
+pub fn apply_water_quality(
+  mut quality_changed: MessageReader<WaterQualityChanged>,
+  mut materials: ResMut<Assets<StandardWaterMaterial>>,
+  tiles: Query<&MeshMaterial3d<StandardWaterMaterial>, With<WaterTile>>,
+) {
+  let Some(quality) = quality_changed.read().last().map(|changed| changed.quality) else {
+    return;
+  };
+  let key: u32 = quality.into();
+  for material in tiles.iter() {
+    let stale = materials
+      .get(&material.0)
+      .is_some_and(|mat| mat.extension.quality != key);
+    if stale {
+      if let Some(mat) = materials.get_mut(&material.0) {
+        mat.extension.quality = key;
+      }
+    }
+  }
 }
 
 #[cfg(feature = "easings")]
@@ -395,8 +935,13 @@ pub fn update_water_height(
   mut commands: Commands,
   settings: Res<WaterSettings>,
   easing_settings: Res<WaterHeightEasingSettings>,
+  tide: Option<Res<Tide>>,
   water_transforms: Query<(Entity, &Transform), With<WaterTile>>,
 ) {
+  // The tide moves the tiles.
+  if tide.is_some() {
+    return;
+  }
   for (entity, transform) in water_transforms.iter() {
     // Apply height easing if height has changed
     if transform.translation.y != settings.height {
@@ -415,30 +960,147 @@ pub fn update_water_height(
   }
 }
 
+/// Water bodies driven by their own preset or timeline.
+type WithOwnSettings = Or<(With<WaterPresetHandle>, With<WaterTimeline>)>;
+
+/// `material` updated from `settings`, keeping the flow (`WaterFlow`) and amplitude
+/// (`WaterVolume`) owned by its water body.
+///
+/// Returns `None` if the material is already up to date.
+fn updated_material(
+  settings: &WaterSettings,
+  material: &StandardWaterMaterial,
+  own_flow: bool,
+  own_amplitude: bool,
+) -> Option<StandardWaterMaterial> {
+  let mut updated = material.clone();
+  settings.apply_base(&mut updated.base);
+  settings.apply_extension(&mut updated.extension);
+  let (current, extension) = (&material.extension, &mut updated.extension);
+  if own_flow {
+    extension.flow_velocity = current.flow_velocity;
+    extension.flow_map = current.flow_map.clone();
+    extension.flow_map_scale = current.flow_map_scale;
+    extension.flow_map_offset = current.flow_map_offset;
+    extension.flow_map_size = current.flow_map_size;
+    extension.flow_period = current.flow_period;
+  }
+  if own_amplitude {
+    extension.amplitude = current.amplitude;
+  }
+  let (base, current) = (&updated.base, &material.base);
+  let changed = base.base_color != current.base_color
+    || base.alpha_mode != current.alpha_mode
+    || base.specular_transmission != current.specular_transmission
+    || updated.extension != material.extension;
+  changed.then_some(updated)
+}
+
 pub fn update_materials(
   settings: Res<WaterSettings>,
   mut materials: ResMut<Assets<StandardWaterMaterial>>,
+  bodies: Query<&MeshMaterial3d<StandardWaterMaterial>, WithOwnSettings>,
+  flows: Query<&MeshMaterial3d<StandardWaterMaterial>, With<WaterFlow>>,
+  volumes: Query<&MeshMaterial3d<StandardWaterMaterial>, With<WaterVolumeSurface>>,
 ) {
   if !settings.update_materials {
     return;
   }
-  for (_, mat) in materials.iter_mut() {
-    mat.base.base_color = settings.base_color;
-    mat.base.alpha_mode = settings.alpha_mode;
-    mat.extension.amplitude = settings.amplitude;
-    mat.extension.clarity = settings.clarity;
-    mat.extension.deep_color = settings.deep_color;
-    mat.extension.shallow_color = settings.shallow_color;
-    mat.extension.edge_color = settings.edge_color;
-    mat.extension.edge_scale = settings.edge_scale;
-    mat.extension.quality = settings.water_quality.into();
+  let skip: Vec<_> = bodies.iter().map(|material| material.id()).collect();
+  let flows: Vec<_> = flows.iter().map(|material| material.id()).collect();
+  let volumes: Vec<_> = volumes.iter().map(|material| material.id()).collect();
+  // Only touch the changed materials, `get_mut` re-uploads them.
+  let stale: Vec<_> = materials
+    .iter()
+    .filter(|(id, _)| !skip.contains(id))
+    .filter_map(|(id, mat)| {
+      updated_material(&settings, mat, flows.contains(&id), volumes.contains(&id))
+        .map(|updated| (id, updated))
+    })
+    .collect();
+  for (id, updated) in stale {
+    if let Some(mat) = materials.get_mut(id) {
+      *mat = updated;
+    }
+  }
+}
+
+/// Send `WaterHeightChanged` and `WaterQualityChanged` messages.
+pub fn send_water_settings_messages(
+  settings: Res<WaterSettings>,
+  mut last: Local<Option<(f32, WaterQuality)>>,
+  mut height_changed: MessageWriter<WaterHeightChanged>,
+  mut quality_changed: MessageWriter<WaterQualityChanged>,
+) {
+  let (height, quality) = (settings.height, settings.water_quality);
+  if let Some((previous_height, previous_quality)) = last.replace((height, quality)) {
+    if previous_height != height {
+      height_changed.write(WaterHeightChanged {
+        previous: previous_height,
+        height,
+      });
+    }
+    if previous_quality != quality {
+      quality_changed.write(WaterQualityChanged {
+        previous: previous_quality,
+        quality,
+      });
+    }
+  }
+}
+
+/// Advance the global wave clock.
+pub fn update_wave_clock(
+  time: Res<Time>,
+  mut wave_state: ResMut<GlobalWaveState>,
+  mut last_clock: Local<Option<f32>>,
+) {
+  let now = time.elapsed_secs_wrapped();
+  // The state was replaced, e.g. loaded from a saved scene.
+  if last_clock.is_some_and(|last| last != wave_state.clock_time) {
+    wave_state.restore_clock(now);
+  } else {
+    wave_state.update_clock(now);
   }
+  *last_clock = Some(now);
 }
 
-/// Sync wave direction from WaterSettings to WaveDirection components.
+/// Apply `WaterSettings::wave_speed` to the global wave time.
+pub fn update_wave_speed(
+  time: Res<Time>,
+  settings: Res<WaterSettings>,
+  mut wave_state: ResMut<GlobalWaveState>,
+) {
+  if wave_state.wave_speed != settings.wave_speed {
+    wave_state.set_wave_speed(time.elapsed_secs_wrapped(), settings.wave_speed);
+  }
+}
+
+/// Apply the global wave time to the water materials.
+pub fn apply_wave_time(
+  wave_state: Res<GlobalWaveState>,
+  mut materials: ResMut<Assets<StandardWaterMaterial>>,
+) {
+  let stale: Vec<_> = materials
+    .iter()
+    .filter(|(_, mat)| {
+      mat.extension.wave_speed != wave_state.wave_speed
+        || mat.extension.wave_time_offset != wave_state.time_offset
+    })
+    .map(|(id, _)| id)
+    .collect();
+  for id in stale {
+    if let Some(mat) = materials.get_mut(id) {
+      mat.extension.wave_speed = wave_state.wave_speed;
+      mat.extension.wave_time_offset = wave_state.time_offset;
+    }
+  }
+}
+
+/// Sync wave direction from WaterSettings to WaveDirection components (water tiles and lakes).
 pub fn sync_wave_direction_settings(
   settings: Res<WaterSettings>,
-  mut water_tiles: Query<&mut WaveDirection, With<WaterTile>>,
+  mut water_tiles: Query<&mut WaveDirection, (Without<WaterPresetHandle>, Without<WaterTimeline>)>,
 ) {
   for mut wave_dir in water_tiles.iter_mut() {
     wave_dir.set_target(settings.wave_direction);
@@ -446,13 +1108,14 @@ pub fn sync_wave_direction_settings(
   }
 }
 
-/// Update global wave state from the first tile's WaveDirection (for physics).
+/// Update global wave state from the first tile's (or lake's) WaveDirection (for physics).
 pub fn update_global_wave_state(
   mut global_state: ResMut<GlobalWaveState>,
   water_tiles: Query<&WaveDirection, With<WaterTile>>,
+  lakes: Query<&WaveDirection, With<Lake>>,
 ) {
   // Use the first tile's state (without tile_offset) as the global state
-  if let Some(wave_dir) = water_tiles.iter().next() {
+  if let Some(wave_dir) = water_tiles.iter().next().or_else(|| lakes.iter().next()) {
     global_state.dir_a = wave_dir.dir_a();
     global_state.dir_b = wave_dir.dir_b();
     global_state.blend = wave_dir.blend();
@@ -462,20 +1125,51 @@ pub fn update_global_wave_state(
 /// Update wave direction spring simulation each frame.
 pub fn update_wave_direction(
   time: Res<Time>,
-  mut water_tiles: Query<&mut WaveDirection, With<WaterTile>>,
+  mut water_tiles: Query<(Entity, &mut WaveDirection, Has<WaterTile>)>,
+  mut changed: MessageWriter<WaveDirectionChanged>,
+  mut settled: MessageWriter<WaveDirectionSettled>,
 ) {
   let dt = time.delta_secs();
-  for mut wave_dir in water_tiles.iter_mut() {
+  // One message for all the water tiles.
+  let (mut tiles_changed, mut tiles_settled) = (false, false);
+  for (entity, mut wave_dir, is_tile) in water_tiles.iter_mut() {
+    let body = (!is_tile).then_some(entity);
+    if std::mem::take(&mut wave_dir.started) && !(is_tile && tiles_changed) {
+      tiles_changed |= is_tile;
+      changed.write(WaveDirectionChanged {
+        body,
+        from: wave_dir.dir_a,
+        to: wave_dir.dir_b,
+        duration: wave_dir.blend_duration,
+      });
+    }
+    let was_settled = wave_dir.is_settled();
     wave_dir.update(dt);
+    if !was_settled && wave_dir.is_settled() && !(is_tile && tiles_settled) {
+      tiles_settled |= is_tile;
+      settled.write(WaveDirectionSettled {
+        body,
+        direction: wave_dir.dir_b,
+      });
+    }
   }
 }
 
-/// Apply wave direction to materials each frame.
+/// Apply wave direction to materials when it changes.
 pub fn apply_wave_direction(
   mut materials: ResMut<Assets<StandardWaterMaterial>>,
   water_tiles: Query<(&MeshMaterial3d<StandardWaterMaterial>, &WaveDirection)>,
 ) {
   for (material_handle, wave_dir) in water_tiles.iter() {
+    // Only re-upload changed materials, the water tiles share one material.
+    let stale = materials.get(&material_handle.0).is_some_and(|mat| {
+      mat.extension.wave_dir_a != wave_dir.dir_a()
+        || mat.extension.wave_dir_b != wave_dir.dir_b()
+        || mat.extension.wave_blend != wave_dir.blend()
+    });
+    if !stale {
+      continue;
+    }
     if let Some(mat) = materials.get_mut(&material_handle.0) {
       mat.extension.wave_dir_a = wave_dir.dir_a();
       mat.extension.wave_dir_b = wave_dir.dir_b();
@@ -492,7 +1186,44 @@ impl Plugin for WaterPlugin {
     let app = app
       .init_resource::<WaterSettings>()
       .init_resource::<GlobalWaveState>()
+      .init_resource::<WaterWakes>()
       .register_type::<WaterSettings>()
+      .register_type::<GlobalWaveState>()
+      .register_type::<WaveDirection>()
+      .register_type::<WaterTiles>()
+      .register_type::<WaterTile>()
+      .register_type::<WaterVolumeSurface>()
+      .register_type::<WaterVolumeWalls>()
+      .register_type::<WaterFlow>()
+      .register_type::<River>()
+      .register_type::<Lake>()
+      .register_type::<WaterVolume>()
+      .register_type::<WaterSphere>()
+      .register_type::<WaterRipples>()
+      .register_type::<RippleFocus>()
+      .register_type::<RippleEmitter>()
+      .register_type::<Wake>()
+      .register_type::<WaterWakes>()
+      .register_type::<WaterContact>()
+      .register_type::<WaterSplashSettings>()
+      .register_type::<SplashParticle>()
+      .register_type::<Wind>()
+      .register_type::<Tide>()
+      .register_type::<WaterPresetHandle>()
+      .register_type::<WaterBlend>()
+      .register_type::<WaterTimeline>()
+      .register_type::<WaterReconcile>()
+      .init_asset::<WaterPreset>()
+      .init_asset_loader::<WaterPresetLoader>()
+      .add_message::<HighTide>()
+      .add_message::<LowTide>()
+      .add_message::<WaterEntered>()
+      .add_message::<WaterExited>()
+      .add_message::<Submerged>()
+      .add_message::<WaveDirectionChanged>()
+      .add_message::<WaveDirectionSettled>()
+      .add_message::<WaterHeightChanged>()
+      .add_message::<WaterQualityChanged>()
       .add_plugins(WaterMaterialPlugin)
       .add_systems(Startup, setup_water);
 
@@ -510,13 +1241,435 @@ impl Plugin for WaterPlugin {
     app.add_systems(
       Update,
       (
-        update_materials.run_if(resource_changed::<WaterSettings>),
+        // Environment.
+        (
+          apply_water_presets,
+          update_water_blend.run_if(resource_exists::<WaterBlend>),
+          update_water_timelines,
+          apply_wind.run_if(resource_exists::<Wind>),
+          update_global_tide.run_if(resource_exists::<Tide>),
+          update_tides,
+        )
+          .chain(),
+        update_wave_clock,
+        (
+          send_water_settings_messages,
+          apply_water_quality,
+          update_water_grid,
+          update_wave_speed,
+          update_materials,
+        )
+          .chain()
+          .run_if(resource_changed::<WaterSettings>),
+        apply_water_flow,
         sync_wave_direction_settings.run_if(resource_changed::<WaterSettings>),
         update_wave_direction,
+        reconcile_water.run_if(resource_exists::<WaterReconcile>),
         update_global_wave_state,
         apply_wave_direction,
+        apply_wave_time,
+        // Water bodies.
+        (
+          setup_water_volumes,
+          update_water_volume_level,
+          update_water_volume_meshes,
+          apply_water_sphere,
+        )
+          .chain(),
+        (emit_water_ripples, update_water_ripples)
+          .chain()
+          .run_if(resource_exists::<WaterRipples>),
+        update_wakes,
+        apply_water_wakes.run_if(resource_changed::<WaterWakes>),
+        update_water_contacts,
+        (spawn_water_splashes, update_water_splashes)
+          .chain()
+          .run_if(resource_exists::<WaterSplashSettings>),
       )
         .chain(),
     );
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use bevy::{
+    scene::{serde::SceneDeserializer, DynamicSceneBuilder},
+    time::TimeUpdateStrategy,
+  };
+  use serde::de::DeserializeSeed;
+
+  use super::*;
+
+  fn scene_app() -> App {
+    let mut app = App::new();
+    app
+      .register_type::<GlobalWaveState>()
+      .register_type::<WaveDirection>()
+      .register_type::<WaterTiles>()
+      .register_type::<WaterTile>()
+      .register_type::<WaterVolume>()
+      .register_type::<WaterVolumeSurface>()
+      .register_type::<WaterVolumeWalls>()
+      .register_type::<Tide>()
+      .register_type::<WaterRipples>()
+      .register_type::<Wind>()
+      .register_type::<ChildOf>();
+    app
+  }
+
+  /// Save the water entities and resources to RON.
+  fn save(world: &mut World) -> String {
+    let entities: Vec<_> = world.query::<Entity>().iter(world).collect();
+    let scene = DynamicSceneBuilder::from_world(world)
+      .deny_all()
+      .allow_component::<WaveDirection>()
+      .allow_component::<WaterTiles>()
+      .allow_component::<WaterTile>()
+      .allow_component::<WaterVolume>()
+      .allow_component::<WaterVolumeSurface>()
+      .allow_component::<WaterVolumeWalls>()
+      .allow_component::<ChildOf>()
+      .allow_resource::<GlobalWaveState>()
+      .allow_resource::<Tide>()
+      .allow_resource::<WaterRipples>()
+      .allow_resource::<Wind>()
+      .extract_entities(entities.into_iter())
+      .extract_resources()
+      .build();
+    let registry = world.resource::<AppTypeRegistry>().read();
+    scene.serialize(&registry).unwrap()
+  }
+
+  /// Load the RON into `world`, returns the saved to loaded entity map.
+  fn load(world: &mut World, ron: &str) -> bevy::ecs::entity::EntityHashMap<Entity> {
+    let scene = {
+      let registry = world.resource::<AppTypeRegistry>().read();
+      let mut deserializer = ron::de::Deserializer::from_str(ron).unwrap();
+      SceneDeserializer {
+        type_registry: &registry,
+      }
+      .deserialize(&mut deserializer)
+      .unwrap()
+    };
+    let mut entity_map = default();
+    scene.write_to_world(world, &mut entity_map).unwrap();
+    entity_map
+  }
+
+  #[test]
+  fn quality_and_grid_change_together() {
+    let mut app = App::new();
+    app
+      .insert_resource(WaterSettings {
+        water_quality: WaterQuality::Basic,
+        spawn_tiles: Some(UVec2::new(2, 2)),
+        ..default()
+      })
+      .init_resource::<GlobalWaveState>()
+      .init_resource::<Assets<Mesh>>()
+      .init_resource::<Assets<StandardWaterMaterial>>()
+      .add_message::<WaterHeightChanged>()
+      .add_message::<WaterQualityChanged>()
+      .add_systems(Startup, setup_water)
+      .add_systems(
+        Update,
+        (
+          send_water_settings_messages,
+          apply_water_quality,
+          update_water_grid,
+        )
+          .chain(),
+      );
+    // Don't rely on a sync point between the systems.
+    app.edit_schedule(Update, |schedule| {
+      schedule.set_build_settings(bevy::ecs::schedule::ScheduleBuildSettings {
+        auto_insert_apply_deferred: false,
+        ..default()
+      });
+    });
+    app.update();
+
+    let mut settings = app.world_mut().resource_mut::<WaterSettings>();
+    settings.water_quality = WaterQuality::High;
+    settings.spawn_tiles = Some(UVec2::new(3, 3));
+    app.update();
+
+    let world = app.world_mut();
+    let settings = world.resource::<WaterSettings>().clone();
+    let vertices = water_tile_mesh(&settings).count_vertices();
+    let mut tiles = world.query_filtered::<(&Mesh3d, Has<NotShadowReceiver>), With<WaterTile>>();
+    let tiles: Vec<_> = tiles
+      .iter(world)
+      .map(|(mesh, no_shadows)| (mesh.clone(), no_shadows))
+      .collect();
+    assert_eq!(tiles.len(), 9);
+    let meshes = world.resource::<Assets<Mesh>>();
+    for (mesh, no_shadows) in tiles {
+      assert_eq!(meshes.get(&mesh.0).unwrap().count_vertices(), vertices);
+      assert!(!no_shadows);
+    }
+  }
+
+  #[test]
+  fn body_materials_keep_their_flow_and_amplitude() {
+    let mut app = App::new();
+    app
+      .init_resource::<WaterSettings>()
+      .init_resource::<Assets<StandardWaterMaterial>>()
+      .add_systems(Update, update_materials);
+    let settings = app.world().resource::<WaterSettings>().clone();
+    let mut materials = app
+      .world_mut()
+      .resource_mut::<Assets<StandardWaterMaterial>>();
+    let grid = materials.add(settings.new_grid_material());
+    let mut river = settings.new_material(Vec2::ZERO, Vec2::ONE);
+    let flow = WaterFlow::from_velocity(Vec2::new(0.0, 3.0));
+    flow.apply_to(&mut river.extension);
+    let river = materials.add(river);
+    let mut volume = settings.new_material(Vec2::ZERO, Vec2::ONE);
+    volume.extension.amplitude = 0.05;
+    let volume = materials.add(volume);
+    app.world_mut().spawn((flow, MeshMaterial3d(river.clone())));
+    app.world_mut().spawn((
+      WaterVolumeSurface {
+        volume: Entity::PLACEHOLDER,
+      },
+      MeshMaterial3d(volume.clone()),
+    ));
+
+    let mut settings = app.world_mut().resource_mut::<WaterSettings>();
+    settings.amplitude = 2.0;
+    settings.clarity = 0.5;
+    app.update();
+
+    let world = app.world();
+    let settings = world.resource::<WaterSettings>();
+    let materials = world.resource::<Assets<StandardWaterMaterial>>();
+    let (grid, river, volume) = (
+      materials.get(&grid).unwrap(),
+      materials.get(&river).unwrap(),
+      materials.get(&volume).unwrap(),
+    );
+    assert_eq!(grid.extension.amplitude, 2.0);
+    assert_eq!(river.extension.amplitude, 2.0);
+    assert_eq!(river.extension.flow_velocity, Vec2::new(0.0, 3.0));
+    assert_eq!(volume.extension.amplitude, 0.05);
+    assert_eq!(volume.extension.clarity, 0.5);
+    // Up to date, the next settings change doesn't re-upload them.
+    assert!(updated_material(settings, river, true, false).is_none());
+    assert!(updated_material(settings, volume, false, true).is_none());
+  }
+
+  #[test]
+  fn invalid_runtime_settings_keep_the_grid() {
+    let mut settings = WaterSettings {
+      water_quality: WaterQuality::Basic,
+      spawn_tiles: Some(UVec2::new(2, 2)),
+      ..default()
+    };
+    assert!(settings.validate().is_ok());
+    // Not the active quality, but it can be switched to at runtime.
+    settings.tile_subdivisions.ultra = MAX_TILE_SUBDIVISIONS + 1;
+    assert!(matches!(
+      settings.validate(),
+      Err(WaterSettingsError::TooManySubdivisions(_))
+    ));
+    settings.tile_subdivisions.ultra = 0;
+
+    let mut app = App::new();
+    app
+      .insert_resource(settings)
+      .init_resource::<GlobalWaveState>()
+      .init_resource::<Assets<Mesh>>()
+      .init_resource::<Assets<StandardWaterMaterial>>()
+      .add_systems(Startup, setup_water)
+      .add_systems(Update, update_water_grid);
+    app.update();
+    let mut tiles = app.world_mut().query_filtered::<Entity, With<WaterTile>>();
+    let before: Vec<_> = tiles.iter(app.world()).collect();
+    assert_eq!(before.len(), 4);
+
+    app.world_mut().resource_mut::<WaterSettings>().tile_size = 0.0;
+    app.update();
+    let after: Vec<_> = tiles.iter(app.world()).collect();
+    assert_eq!(before, after);
+
+    // A valid change after the invalid one is still applied.
+    app.world_mut().resource_mut::<WaterSettings>().tile_size = 128.0;
+    app.update();
+    let rebuilt: Vec<_> = tiles.iter(app.world()).collect();
+    assert_eq!(rebuilt.len(), 4);
+    assert!(rebuilt.iter().all(|tile| !before.contains(tile)));
+  }
+
+  #[test]
+  fn rain_stays_compiled_in() {
+    let mut settings = WaterSettings::default();
+    let mut material = settings.new_grid_material();
+    assert!(!material.extension.rain_enabled);
+    settings.rain_intensity = 0.5;
+    settings.apply_extension(&mut material.extension);
+    assert!(material.extension.rain_enabled);
+    // The rain stops, the shader variant is kept.
+    settings.rain_intensity = 0.0;
+    settings.apply_extension(&mut material.extension);
+    assert!(material.extension.rain_enabled);
+    assert!(settings.is_applied(&material));
+  }
+
+  #[test]
+  fn unchanged_materials_are_skipped() {
+    let mut settings = WaterSettings::default();
+    let material = settings.new_grid_material();
+    assert!(settings.is_applied(&material));
+
+    settings.amplitude += 1.0;
+    assert!(!settings.is_applied(&material));
+    settings.amplitude -= 1.0;
+    settings.refraction = !settings.refraction;
+    assert!(!settings.is_applied(&material));
+  }
+
+  #[test]
+  fn one_wave_direction_message_for_the_tiles() {
+    let mut app = App::new();
+    app
+      .add_plugins(MinimalPlugins)
+      .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+        500,
+      )))
+      .add_message::<WaveDirectionChanged>()
+      .add_message::<WaveDirectionSettled>()
+      .add_systems(Update, update_wave_direction);
+    for _ in 0..36 {
+      app.world_mut().spawn((
+        WaterTile::default(),
+        WaveDirection::with_duration(Vec2::X, 1.0),
+      ));
+    }
+    let lake = app
+      .world_mut()
+      .spawn(WaveDirection::with_duration(Vec2::X, 1.0))
+      .id();
+    let world = app.world_mut();
+    for mut wave_dir in world.query::<&mut WaveDirection>().iter_mut(world) {
+      wave_dir.set_target(Vec2::Y);
+    }
+
+    let (mut changed, mut settled) = (Vec::new(), Vec::new());
+    for _ in 0..5 {
+      app.update();
+      let world = app.world_mut();
+      let mut messages = world.resource_mut::<Messages<WaveDirectionChanged>>();
+      changed.extend(messages.drain().map(|m| m.body));
+      let mut messages = world.resource_mut::<Messages<WaveDirectionSettled>>();
+      settled.extend(messages.drain().map(|m| m.body));
+    }
+    changed.sort();
+    settled.sort();
+    assert_eq!(changed, [None, Some(lake)]);
+    assert_eq!(settled, [None, Some(lake)]);
+  }
+
+  #[test]
+  fn water_state_scene_round_trip() {
+    let mut saved = scene_app();
+    let world = saved.world_mut();
+
+    let mut wave_state = GlobalWaveState::default();
+    wave_state.set_wave_speed(3.0, 1.5);
+    wave_state.update_clock(10.0);
+    wave_state.dir_a = Vec2::X;
+    wave_state.dir_b = Vec2::Y;
+    wave_state.blend = 0.25;
+    world.insert_resource(wave_state);
+    let mut tide = Tide::sine(2.0, 1.0, 60.0);
+    tide.update(12.5);
+    world.insert_resource(tide.clone());
+    let mut ripples = WaterRipples::new(32, 0.5);
+    ripples.set_center(Vec2::new(10.0, 4.0));
+    ripples.disturb(Vec2::new(10.0, 4.0), 1.0, 0.5);
+    ripples.step(0.1);
+    world.insert_resource(ripples.clone());
+    let mut wind = Wind::new(Vec2::X, 15.0);
+    wind.update(1.0);
+    wind.speed = 20.0;
+    wind.update(2.0);
+    world.insert_resource(wind.clone());
+
+    let mut wave_dir = WaveDirection::with_duration(Vec2::X, 4.0);
+    wave_dir.set_target(Vec2::Y);
+    wave_dir.update(1.0);
+    let water = world.spawn(WaterTiles).id();
+    world.spawn((
+      WaterTile::new(1.0, Vec2::new(-256.0, 0.0)),
+      wave_dir,
+      ChildOf(water),
+    ));
+    let volume = world.spawn(WaterVolume::cuboid(Vec3::splat(2.0))).id();
+    world.spawn((WaterVolumeSurface { volume }, ChildOf(volume)));
+    world.spawn((WaterVolumeWalls { volume }, ChildOf(volume)));
+
+    let ron = save(world);
+    assert!(!ron.contains("started"), "transient state is saved: {ron}");
+
+    let mut loaded = scene_app();
+    let world = loaded.world_mut();
+    // Occupy the saved entity ids, so they must be remapped.
+    for _ in 0..10 {
+      world.spawn_empty();
+    }
+    let entity_map = load(world, &ron);
+
+    let loaded_state = *world.resource::<GlobalWaveState>();
+    assert_eq!(loaded_state.dir_a, wave_state.dir_a);
+    assert_eq!(loaded_state.dir_b, wave_state.dir_b);
+    assert_eq!(loaded_state.blend, wave_state.blend);
+    assert_eq!(loaded_state.wave_speed, wave_state.wave_speed);
+    assert_eq!(loaded_state.time_offset, wave_state.time_offset);
+    assert_eq!(loaded_state.wave_time, wave_state.wave_time);
+    assert_eq!(loaded_state.clock_time, wave_state.clock_time);
+    let loaded_tide = world.resource::<Tide>();
+    assert_eq!(loaded_tide.time, tide.time);
+    assert_eq!(loaded_tide.height(), tide.height());
+    let loaded_ripples = world.resource::<WaterRipples>();
+    assert_eq!(loaded_ripples.resolution(), ripples.resolution());
+    assert_eq!(loaded_ripples.offset(), ripples.offset());
+    for p in [
+      Vec2::new(10.0, 4.0),
+      Vec2::new(10.5, 4.25),
+      Vec2::new(9.0, 5.0),
+    ] {
+      assert_ne!(ripples.height_at(p), 0.0);
+      assert_eq!(loaded_ripples.height_at(p), ripples.height_at(p));
+    }
+    let loaded_wind = world.resource::<Wind>();
+    assert_ne!(wind.sea_speed(), wind.speed);
+    assert_eq!(loaded_wind.speed, wind.speed);
+    assert_eq!(loaded_wind.sea_speed(), wind.sea_speed());
+    assert_eq!(loaded_wind.current_speed(), wind.current_speed());
+
+    let (tile, loaded_dir, child_of) = world
+      .query::<(&WaterTile, &WaveDirection, &ChildOf)>()
+      .single(world)
+      .unwrap();
+    assert_eq!(tile.offset, Vec2::new(-256.0, 0.0));
+    assert_eq!(loaded_dir.dir_a(), wave_dir.dir_a());
+    assert_eq!(loaded_dir.dir_b(), wave_dir.dir_b());
+    assert_eq!(loaded_dir.blend(), wave_dir.blend());
+    assert!(!loaded_dir.started);
+    assert_eq!(child_of.parent(), entity_map[&water]);
+    assert!(world.get::<WaterTiles>(entity_map[&water]).is_some());
+
+    let loaded_volume = entity_map[&volume];
+    assert_ne!(loaded_volume, volume);
+    assert!(world.get::<WaterVolume>(loaded_volume).is_some());
+    let surface = world.query::<&WaterVolumeSurface>().single(world).unwrap();
+    assert_eq!(surface.volume, loaded_volume);
+    let walls = world.query::<&WaterVolumeWalls>().single(world).unwrap();
+    assert_eq!(walls.volume, loaded_volume);
+  }
+}
@@ -8,9 +8,11 @@ use bevy::{
   shader::*,
 };
 
+use crate::{WakeData, MAX_WATER_WAKES};
+
 pub type StandardWaterMaterial = ExtendedMaterial<StandardMaterial, WaterMaterial>;
 
-#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone, PartialEq)]
 #[uniform(100, WaterMaterialUniform)]
 #[bind_group_data(WaterMaterialKey)]
 #[reflect(Default, Debug)]
@@ -29,6 +31,10 @@ pub struct WaterMaterial {
   pub amplitude: f32,
   pub coord_offset: Vec2,
   pub coord_scale: Vec2,
+  /// Wave coordinates from the world X/Z position (plus `coord_offset`) instead of the mesh UVs.
+  ///
+  /// Meshes at different positions can share one material, like the water tiles.
+  pub world_coords: bool,
   /// Wave direction A (fading out during transition).
   pub wave_dir_a: Vec2,
   /// Wave direction B (fading in during transition).
@@ -36,6 +42,91 @@ pub struct WaterMaterial {
   /// Blend factor between directions: 0 = fully A, 1 = fully B.
   pub wave_blend: f32,
   pub quality: u32,
+  /// Enable screen-space refraction of the opaque scene below the surface.
+  ///
+  /// Requires the `base` material to be opaque with `specular_transmission > 0.0`,
+  /// so the opaque scene is available to the water shader.
+  pub refraction: bool,
+  /// Strength of the refraction distortion caused by the wave normal.
+  pub refraction_strength: f32,
+  /// Chromatic dispersion of the refraction, 0.0 = none.
+  pub refraction_dispersion: f32,
+  /// Color of light scattered through the wave crests.
+  pub subsurface_color: Color,
+  /// Strength of the subsurface scattering, 0.0 = disabled.
+  pub subsurface_strength: f32,
+  /// Tangent-space normal map for fine ripples layered over the waves.
+  ///
+  /// The one map is sampled as two scrolling layers with different scales and directions.
+  ///
+  /// The image sampler should use `AddressMode::Repeat` (see `ImageReformat::uv_repeat`).
+  #[texture(101)]
+  #[sampler(102)]
+  pub detail_normal_map: Option<Handle<Image>>,
+  /// Strength of the detail normals.
+  pub detail_normal_strength: f32,
+  /// World-space scale of the two detail normal layers.
+  pub detail_normal_scale: Vec2,
+  /// Scroll speed of the detail normals along the wave direction.
+  pub detail_normal_speed: f32,
+  /// Distance from the camera where the detail normals have faded out.
+  pub detail_fade_distance: f32,
+  /// Constant flow velocity in world units per second (see `WaterFlow`).
+  pub flow_velocity: Vec2,
+  /// Flow map.  The red/green channels encode the flow direction (0.5 = no flow).
+  #[texture(103)]
+  #[sampler(104)]
+  pub flow_map: Option<Handle<Image>>,
+  /// Flow velocity for a full flow map value.
+  pub flow_map_scale: f32,
+  /// Wave coordinate of the flow map's corner.
+  pub flow_map_offset: Vec2,
+  /// Size covered by the flow map in wave coordinates.
+  pub flow_map_size: Vec2,
+  /// Period in seconds of the two-phase flow blending.
+  pub flow_period: f32,
+  /// Center of the planet for spherical water (see `WaterSphere`).
+  pub sphere_center: Vec3,
+  /// Radius of the planet, 0.0 = flat water.
+  ///
+  /// Spherical water uses triplanar wave coordinates and displaces the surface away from `sphere_center`.
+  pub sphere_radius: f32,
+  /// Multi-face cube coordinates (see `CubeWaterMeshBuilder`).
+  ///
+  /// The UVs encode the cube face and `coord_scale.x` is the half size of the cube.
+  /// The waves are continuous across the face edges.
+  pub cube: bool,
+  /// Ripple heightfield (see `WaterRipples`), added to the wave height.
+  #[texture(105, sample_type = "float", filterable = false)]
+  pub ripple_map: Option<Handle<Image>>,
+  /// World X/Z position of the ripple heightfield's corner.
+  pub ripple_offset: Vec2,
+  /// World size of the ripple heightfield.
+  pub ripple_size: Vec2,
+  /// Kelvin wakes of moving boats (see `Wake`).
+  pub wakes: [WakeData; MAX_WATER_WAKES],
+  /// Number of used `wakes`.
+  pub wake_count: u32,
+  /// Compile the wakes into the shader.
+  ///
+  /// Set once the first wake is uploaded, so boats starting or stopping only change `wake_count`.
+  pub wakes_enabled: bool,
+  /// Scale of the wave length.
+  pub wave_length: f32,
+  /// Speed of the wave animation.
+  pub wave_speed: f32,
+  /// Offset of the wave time (see `GlobalWaveState::wave_time`).
+  pub wave_time_offset: f32,
+  /// Rain intensity, 0.0 = no rain.
+  pub rain_intensity: f32,
+  /// Compile the rain ripples into the shader.
+  ///
+  /// Set once it starts raining, so rain ramping up from (or down to) 0.0 only changes `rain_intensity`.
+  pub rain_enabled: bool,
+  /// World size of a raindrop ripple.
+  pub rain_ripple_size: f32,
+  /// Strength of the raindrop ripple normals.
+  pub rain_ripple_strength: f32,
 }
 
 impl Default for WaterMaterial {
@@ -50,29 +141,93 @@ impl Default for WaterMaterial {
       amplitude: 1.0,
       coord_offset: Vec2::new(0.0, 0.0),
       coord_scale: Vec2::new(1.0, 1.0),
+      world_coords: false,
       wave_dir_a: default_dir,
       wave_dir_b: default_dir,
       wave_blend: 1.0,
       quality: 4,
+      refraction: false,
+      refraction_strength: 0.05,
+      refraction_dispersion: 0.1,
+      subsurface_color: Color::srgba(0.1, 0.75, 0.65, 1.0),
+      subsurface_strength: 0.5,
+      detail_normal_map: None,
+      detail_normal_strength: 0.5,
+      detail_normal_scale: Vec2::new(0.25, 0.1),
+      detail_normal_speed: 0.5,
+      detail_fade_distance: 100.0,
+      flow_velocity: Vec2::ZERO,
+      flow_map: None,
+      flow_map_scale: 1.0,
+      flow_map_offset: Vec2::splat(-512.0),
+      flow_map_size: Vec2::splat(1024.0),
+      flow_period: 8.0,
+      sphere_center: Vec3::ZERO,
+      sphere_radius: 0.0,
+      cube: false,
+      ripple_map: None,
+      ripple_offset: Vec2::ZERO,
+      ripple_size: Vec2::ONE,
+      wakes: [WakeData::default(); MAX_WATER_WAKES],
+      wake_count: 0,
+      wakes_enabled: false,
+      wave_length: 1.0,
+      wave_speed: 1.0,
+      wave_time_offset: 0.0,
+      rain_intensity: 0.0,
+      rain_enabled: false,
+      rain_ripple_size: 0.4,
+      rain_ripple_strength: 0.4,
     }
   }
 }
 
+impl WaterMaterial {
+  /// The wave coordinates are flat world X/Z positions (see `world_coords`).
+  ///
+  /// Only these materials get the world-space ripples and wakes.
+  pub fn is_world_aligned(&self) -> bool {
+    self.world_coords && self.sphere_radius <= 0.0 && !self.cube
+  }
+}
+
 #[derive(Copy, Clone, Hash, Eq, PartialEq)]
 pub struct WaterMaterialKey {
   quality: u32,
+  refraction: bool,
+  detail_normals: bool,
+  flow: bool,
+  flow_map: bool,
+  sphere: bool,
+  cube: bool,
+  world_coords: bool,
+  ripples: bool,
+  wakes: bool,
+  rain: bool,
 }
 
 impl From<&WaterMaterial> for WaterMaterialKey {
   fn from(material: &WaterMaterial) -> WaterMaterialKey {
     WaterMaterialKey {
       quality: material.quality,
+      refraction: material.refraction,
+      detail_normals: material.detail_normal_map.is_some(),
+      flow: material.flow_map.is_some() || material.flow_velocity != Vec2::ZERO,
+      flow_map: material.flow_map.is_some(),
+      sphere: material.sphere_radius > 0.0,
+      cube: material.cube,
+      world_coords: material.world_coords,
+      ripples: material.ripple_map.is_some() && material.is_world_aligned(),
+      wakes: material.wakes_enabled && material.is_world_aligned(),
+      rain: material.rain_enabled,
     }
   }
 }
 
 #[derive(Clone, Default, ShaderType)]
 pub struct WaterMaterialUniform {
+  // First, so the struct array is aligned to 16 bytes.
+  pub wakes: [WakeData; MAX_WATER_WAKES],
   pub deep_color: Vec4,
   pub shallow_color: Vec4,
   pub edge_color: Vec4,
@@ -84,6 +239,30 @@ pub struct WaterMaterialUniform {
   pub wave_blend: f32,
   pub wave_dir_a: Vec2,
   pub wave_dir_b: Vec2,
+  pub refraction_strength: f32,
+  pub refraction_dispersion: f32,
+  pub subsurface_strength: f32,
+  pub subsurface_color: Vec4,
+  pub detail_normal_scale: Vec2,
+  pub detail_normal_strength: f32,
+  pub detail_normal_speed: f32,
+  pub detail_fade_distance: f32,
+  pub flow_velocity: Vec2,
+  pub flow_map_offset: Vec2,
+  pub flow_map_size: Vec2,
+  pub flow_map_scale: f32,
+  pub flow_period: f32,
+  pub sphere_center: Vec3,
+  pub sphere_radius: f32,
+  pub ripple_offset: Vec2,
+  pub ripple_size: Vec2,
+  pub wake_count: u32,
+  pub rain_intensity: f32,
+  pub rain_ripple_size: f32,
+  pub rain_ripple_strength: f32,
+  pub wave_length: f32,
+  pub wave_speed: f32,
+  pub wave_time_offset: f32,
 }
 
 impl AsBindGroupShaderType<WaterMaterialUniform> for WaterMaterial {
@@ -100,6 +279,31 @@ impl AsBindGroupShaderType<WaterMaterialUniform> for WaterMaterial {
       wave_dir_a: self.wave_dir_a,
       wave_dir_b: self.wave_dir_b,
       wave_blend: self.wave_blend,
+      refraction_strength: self.refraction_strength,
+      refraction_dispersion: self.refraction_dispersion,
+      subsurface_strength: self.subsurface_strength,
+      subsurface_color: self.subsurface_color.to_linear().to_vec4(),
+      detail_normal_scale: self.detail_normal_scale,
+      detail_normal_strength: self.detail_normal_strength,
+      detail_normal_speed: self.detail_normal_speed,
+      detail_fade_distance: self.detail_fade_distance,
+      flow_velocity: self.flow_velocity,
+      flow_map_offset: self.flow_map_offset,
+      flow_map_size: self.flow_map_size,
+      flow_map_scale: self.flow_map_scale,
+      flow_period: self.flow_period,
+      sphere_center: self.sphere_center,
+      sphere_radius: self.sphere_radius,
+      ripple_offset: self.ripple_offset,
+      ripple_size: self.ripple_size,
+      wake_count: self.wake_count,
+      wakes: self.wakes,
+      rain_intensity: self.rain_intensity,
+      rain_ripple_size: self.rain_ripple_size,
+      rain_ripple_strength: self.rain_ripple_strength,
+      wave_length: self.wave_length,
+      wave_speed: self.wave_speed,
+      wave_time_offset: self.wave_time_offset,
     }
   }
 }
@@ -171,11 +375,48 @@ impl MaterialExtension for WaterMaterial {
     _layout: &MeshVertexBufferLayoutRef,
     key: MaterialExtensionKey<Self>,
   ) -> Result<(), SpecializedMeshPipelineError> {
-    let quality = ShaderDefVal::UInt(String::from("QUALITY"), key.bind_group_data.quality);
+    let mut shader_defs = vec![ShaderDefVal::UInt(
+      String::from("QUALITY"),
+      key.bind_group_data.quality,
+    )];
+    if key.bind_group_data.flow {
+      shader_defs.push("WATER_FLOW".into());
+    }
+    if key.bind_group_data.flow_map {
+      shader_defs.push("WATER_FLOW_MAP".into());
+    }
+    if key.bind_group_data.sphere {
+      shader_defs.push("WATER_SPHERE".into());
+    }
+    if key.bind_group_data.cube {
+      shader_defs.push("WATER_CUBE".into());
+    }
+    if key.bind_group_data.world_coords {
+      shader_defs.push("WATER_WORLD_COORDS".into());
+    }
+    if key.bind_group_data.ripples {
+      shader_defs.push("WATER_RIPPLES".into());
+    }
+    if key.bind_group_data.wakes {
+      shader_defs.push("WATER_WAKES".into());
+    }
+    if let Some(fragment) = descriptor.fragment.as_mut() {
+      fragment.shader_defs.extend(shader_defs.iter().cloned());
+    }
+    descriptor.vertex.shader_defs.extend(shader_defs);
     if let Some(fragment) = descriptor.fragment.as_mut() {
-      fragment.shader_defs.push(quality.clone());
+      if key.bind_group_data.refraction {
+        fragment.shader_defs.push("WATER_REFRACTION".into());
+      }
+      // The detail normals are blended in the X/Z plane, not supported on spheres/cubes.
+      let curved = key.bind_group_data.sphere || key.bind_group_data.cube;
+      if key.bind_group_data.detail_normals && !curved {
+        fragment.shader_defs.push("WATER_DETAIL_NORMALS".into());
+      }
+      if key.bind_group_data.rain && !curved {
+        fragment.shader_defs.push("WATER_RAIN".into());
+      }
     }
-    descriptor.vertex.shader_defs.push(quality);
     Ok(())
   }
 }
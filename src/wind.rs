@@ -0,0 +1,206 @@
+use bevy::prelude::*;
+
+use crate::WaterSettings;
+
+/// Wind driving the waves.
+///
+/// Insert this resource to control `WaterSettings::wave_direction`, `amplitude`,
+/// `wave_length` and `wave_speed` from the wind.  The sea builds up (and calms down)
+/// gradually over `development_time`, so a rising wind grows the waves over minutes.
+///
+/// The mapping follows a fully developed sea (Pierson-Moskowitz): the wave height grows
+/// with the square of the wind speed, the wave length with the square of the wind speed
+/// and the wave period with the wind speed.
+#[derive(Resource, Clone, Debug, Reflect)]
+#[reflect(Resource, Default)]
+pub struct Wind {
+  /// Direction the wind blows towards (X/Z plane).
+  pub direction: Vec2,
+  /// Wind speed in meters per second.
+  pub speed: f32,
+  /// Strength of the gusts, 0.0 = steady wind, 1.0 = gusts of +/- 50% of the speed.
+  pub gustiness: f32,
+  /// Time in seconds for the sea to (mostly) catch up with a change of the wind.
+  pub development_time: f32,
+  /// Wind speed where the waves match the default `WaterSettings` (amplitude of 1.0).
+  pub reference_speed: f32,
+  /// Wave amplitude at the `reference_speed`.
+  pub amplitude_scale: f32,
+  /// Minimum change of the wind direction in degrees before the waves turn.
+  pub direction_threshold: f32,
+  /// Minimum time in seconds between updates of the wave settings.
+  ///
+  /// Every update re-uploads all water materials, so the sea is applied in steps.
+  pub update_interval: f32,
+  /// Wind speed the sea has developed to.
+  sea_speed: Option<f32>,
+  /// Time for the gusts.
+  elapsed: f32,
+  /// Time since the wave settings were last updated.
+  since_update: f32,
+}
+
+impl Default for Wind {
+  fn default() -> Self {
+    Self::new(Vec2::new(1.0, 2.0), 10.0)
+  }
+}
+
+impl Wind {
+  /// Wind blowing towards `direction` with `speed` in meters per second.
+  pub fn new(direction: Vec2, speed: f32) -> Self {
+    Self {
+      direction,
+      speed,
+      gustiness: 0.2,
+      development_time: 120.0,
+      reference_speed: 10.0,
+      amplitude_scale: 1.0,
+      direction_threshold: 10.0,
+      update_interval: 0.5,
+      sea_speed: None,
+      elapsed: 0.0,
+      since_update: f32::INFINITY,
+    }
+  }
+
+  /// Set the strength of the gusts.
+  pub fn with_gustiness(mut self, gustiness: f32) -> Self {
+    self.gustiness = gustiness;
+    self
+  }
+
+  /// Set the time for the sea to catch up with the wind.
+  pub fn with_development_time(mut self, development_time: f32) -> Self {
+    self.development_time = development_time;
+    self
+  }
+
+  /// Set the minimum time between updates of the wave settings.
+  pub fn with_update_interval(mut self, update_interval: f32) -> Self {
+    self.update_interval = update_interval;
+    self
+  }
+
+  /// Current wind speed including gusts.
+  pub fn current_speed(&self) -> f32 {
+    let t = self.elapsed;
+    // Sum of sines as a cheap smooth noise in -1.0..1.0.
+    let gust = (t * 0.7).sin() * 0.5 + (t * 1.9 + 1.3).sin() * 0.3 + (t * 4.3 + 2.1).sin() * 0.2;
+    (self.speed * (1.0 + self.gustiness * 0.5 * gust)).max(0.0)
+  }
+
+  /// Current wind velocity including gusts.
+  pub fn current_velocity(&self) -> Vec2 {
+    self.direction.normalize_or_zero() * self.current_speed()
+  }
+
+  /// Wind speed the sea has developed to.
+  pub fn sea_speed(&self) -> f32 {
+    self.sea_speed.unwrap_or(self.speed)
+  }
+
+  /// Wave amplitude, wave length scale and wave speed for a sea developed to `sea_speed`.
+  pub fn wave_params(&self, sea_speed: f32) -> (f32, f32, f32) {
+    let r = sea_speed / self.reference_speed.max(0.001);
+    let amplitude = self.amplitude_scale * r * r;
+    // Keep the wave pattern in a usable range.
+    let r = r.clamp(0.5, 2.0);
+    let wave_length = r * r;
+    // Longer waves travel faster (in world units), but have a longer period.
+    let wave_speed = 1.0 / r;
+    (amplitude, wave_length, wave_speed)
+  }
+
+  /// Advance the gusts and the sea development by `dt` seconds.
+  pub fn update(&mut self, dt: f32) {
+    self.elapsed += dt;
+    let speed = self.current_speed();
+    let sea_speed = match self.sea_speed {
+      Some(sea_speed) if self.development_time > 0.0 => {
+        // Reaches ~95% of the wind in `development_time`.
+        let blend = 1.0 - (-3.0 * dt / self.development_time).exp();
+        sea_speed + (speed - sea_speed) * blend
+      }
+      // The sea starts developed.
+      _ => self.speed,
+    };
+    self.sea_speed = Some(sea_speed);
+  }
+}
+
+/// Update the `WaterSettings` from the `Wind`.
+pub fn apply_wind(time: Res<Time>, mut wind: ResMut<Wind>, mut settings: ResMut<WaterSettings>) {
+  wind.update(time.delta_secs());
+  wind.since_update += time.delta_secs();
+  let (amplitude, wave_length, wave_speed) = wind.wave_params(wind.sea_speed());
+
+  // Only touch the settings for noticeable changes and at most every `update_interval`,
+  // every change updates all materials.
+  let changed =
+    |current: f32, target: f32| (current - target).abs() > current.abs() * 0.001 + 0.0001;
+  if wind.since_update >= wind.update_interval
+    && (changed(settings.amplitude, amplitude)
+      || changed(settings.wave_length, wave_length)
+      || changed(settings.wave_speed, wave_speed))
+  {
+    wind.since_update = 0.0;
+    settings.amplitude = amplitude;
+    settings.wave_length = wave_length;
+    settings.wave_speed = wave_speed;
+  }
+  let direction = wind.direction.normalize_or_zero();
+  let current = settings.wave_direction.normalize_or_zero();
+  if direction != Vec2::ZERO
+    && current.angle_to(direction).abs().to_degrees() > wind.direction_threshold
+  {
+    settings.wave_direction = direction;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use bevy::time::TimeUpdateStrategy;
+
+  use super::*;
+
+  #[derive(Resource, Default)]
+  struct SettingsWrites(u32);
+
+  fn count_writes(settings: Res<WaterSettings>, mut writes: ResMut<SettingsWrites>) {
+    if settings.is_changed() {
+      writes.0 += 1;
+    }
+  }
+
+  #[test]
+  fn gusts_are_rate_limited() {
+    let mut app = App::new();
+    app
+      .add_plugins(MinimalPlugins)
+      .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+        1.0 / 60.0,
+      )))
+      .init_resource::<WaterSettings>()
+      .init_resource::<SettingsWrites>()
+      // Strong gusts and a fast sea, the waves change noticeably every frame.
+      .insert_resource(
+        Wind::new(Vec2::X, 10.0)
+          .with_gustiness(1.0)
+          .with_development_time(1.0),
+      )
+      .add_systems(Update, (apply_wind, count_writes).chain());
+    // Skip the initial settings.
+    app.update();
+    app.world_mut().resource_mut::<SettingsWrites>().0 = 0;
+
+    for _ in 0..600 {
+      app.update();
+    }
+    // 10 seconds with an update interval of 0.5 seconds.
+    let writes = app.world().resource::<SettingsWrites>().0;
+    assert!((1..=20).contains(&writes), "{writes} settings writes");
+  }
+}